@@ -1,4 +1,14 @@
-use crate::{column::ColumnName, driver::PushPrql, sort::Sorted};
+use std::ops::Range;
+
+use crate::{
+    backend::Backend,
+    column::ColumnName,
+    driver::{Driver, PushPrql},
+    filter::Filtered,
+    select::{select_row, SelectRow},
+    sort::Sorted,
+    take::{Taken, TakenRange},
+};
 
 pub struct Aggregate<Query, Expr> {
     pub query: Query,
@@ -16,12 +26,12 @@ impl<Query, Expr> Aggregate<Query, Expr> {
     }
 }
 
-impl<Query, Expr> PushPrql for Aggregate<Query, Expr>
+impl<DB: Backend, Query, Expr> PushPrql<DB> for Aggregate<Query, Expr>
 where
-    Query: PushPrql,
-    Expr: PushPrql,
+    Query: PushPrql<DB>,
+    Expr: PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut crate::driver::Driver) {
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
         self.query.push_to_driver(driver);
         driver.push("\naggregate {");
         for (i, (col, expr)) in self.aggregations.iter().enumerate() {
@@ -36,3 +46,169 @@ where
         driver.push(" }");
     }
 }
+
+/// Applies a pipeline (usually an `aggregate`) to each group of rows that have
+/// the same values in `columns`. The rows of the result have the columns of the
+/// group, followed by the columns of the pipeline.
+pub struct Group<Query, Col, Pipeline> {
+    pub query: Query,
+    pub columns: Vec<Col>,
+    pub pipeline: Pipeline,
+}
+
+impl<Query, Col, Pipeline> Group<Query, Col, Pipeline> {
+    /// Filters the grouped rows, like `HAVING` in SQL.
+    pub fn filter<Filter>(self, filter: Filter) -> Filtered<Self, Filter> {
+        Filtered {
+            query: self,
+            filter,
+        }
+    }
+
+    pub fn sort<Sort>(self, sort: Sort) -> Sorted<Self, Sort> {
+        Sorted { query: self, sort }
+    }
+
+    pub fn take(self, n: usize) -> Taken<Self> {
+        Taken { query: self, n }
+    }
+
+    pub fn take_range(self, range: Range<usize>) -> TakenRange<Self> {
+        TakenRange { query: self, range }
+    }
+
+    /// Selects the columns of the row `R` that the grouped rows are read as.
+    pub fn select_row<R>(self) -> SelectRow<Self, R> {
+        select_row(self)
+    }
+}
+
+impl<DB: Backend, Query, Col, Pipeline> PushPrql<DB> for Group<Query, Col, Pipeline>
+where
+    Query: PushPrql<DB>,
+    Col: PushPrql<DB>,
+    Pipeline: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        self.query.push_to_driver(driver);
+        driver.push("\ngroup {");
+        for (i, col) in self.columns.iter().enumerate() {
+            if i > 0 {
+                driver.push(", ");
+            }
+            col.push_to_driver(driver);
+        }
+        driver.push("} (");
+        self.pipeline.push_to_driver(driver);
+        driver.push("\n)");
+    }
+}
+
+/// The rows of one group, which the pipeline of a `Group` starts from.
+pub struct Grouped {}
+
+impl Grouped {
+    pub fn aggregate<Expr>(self, name: &'static str, expr: Expr) -> Aggregate<Self, Expr> {
+        Aggregate {
+            query: self,
+            aggregations: vec![(ColumnName { name }, expr)],
+        }
+    }
+
+    pub fn sort<Sort>(self, sort: Sort) -> Sorted<Self, Sort> {
+        Sorted { query: self, sort }
+    }
+
+    pub fn take(self, n: usize) -> Taken<Self> {
+        Taken { query: self, n }
+    }
+}
+
+impl<DB: Backend> PushPrql<DB> for Grouped {
+    fn push_to_driver(&self, _driver: &mut Driver<DB>) {}
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        column::col,
+        cond::gt,
+        expr::{array_agg, count, count_distinct, max, min, stddev, sum},
+        from::from,
+        table::table,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_aggregate() {
+        let mut driver = Driver::new();
+        {
+            let (oldest, youngest) = (max(col("age")), min(col("age")));
+            from(table("users"))
+                .filter(gt(col("age"), 18))
+                .aggregate("oldest", &oldest as &dyn PushPrql)
+                .aggregate("youngest", &youngest)
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT MAX(age) AS oldest, MIN(age) AS youngest FROM users WHERE age > $1"
+        );
+    }
+
+    #[test]
+    fn test_group() {
+        let mut driver = Driver::new();
+        {
+            let (n, emails, spread) = (count(), count_distinct(col("email")), stddev(col("score")));
+            from(table("users"))
+                .group([col("age")], |g| {
+                    g.aggregate("n", &n as &dyn PushPrql)
+                        .aggregate("emails", &emails)
+                        .aggregate("spread", &spread)
+                })
+                .filter(gt(col("n"), 1))
+                .sort(col("n").desc())
+                .take(3)
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT age, COUNT(*) AS n, COUNT(DISTINCT email) AS emails, STDDEV(score) AS spread FROM users GROUP BY age HAVING COUNT(*) > $1 ORDER BY n DESC LIMIT 3"
+        );
+    }
+
+    #[test]
+    fn test_sort_group() {
+        let mut driver = Driver::new();
+        {
+            from(table("users"))
+                .sort(col("age").asc())
+                .group([col("team"), col("age")], |g| {
+                    g.aggregate("total", sum(col("score")))
+                })
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT team, age, COALESCE(SUM(score), 0) AS total FROM users GROUP BY team, age"
+        );
+    }
+
+    #[test]
+    fn test_group_array_agg() {
+        let mut driver = Driver::new();
+        {
+            from(table("users"))
+                .group([col("age")], |g| {
+                    g.aggregate("emails", array_agg(col("email")))
+                })
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT age, array_agg(email) AS emails FROM users GROUP BY age"
+        );
+    }
+}
@@ -0,0 +1,284 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use prqlc::sql::Dialect;
+use sqlx::{
+    postgres::PgArguments,
+    types::{Json, JsonValue},
+    ColumnIndex, Database, Decode, Encode, IntoArguments, Postgres, Row, Type, TypeInfo as _,
+    ValueRef as _,
+};
+#[cfg(feature = "sqlite")]
+use sqlx::{sqlite::SqliteArguments, Sqlite};
+use uuid::Uuid;
+
+/// A database that lsor knows how to talk to. Everything that emits PRQL is
+/// generic over the backend, which decides the SQL dialect that the PRQL is
+/// compiled into and the values that can always be bound and decoded.
+pub trait Backend:
+    BackendArguments
+    + Encodes<bool>
+    + Encodes<i32>
+    + Encodes<i64>
+    + Encodes<f32>
+    + Encodes<f64>
+    + Encodes<String>
+    + Encodes<Vec<u8>>
+    + Encodes<Uuid>
+    + Encodes<DateTime<Utc>>
+    + Encodes<NaiveDateTime>
+    + Encodes<NaiveDate>
+    + Encodes<Json<JsonValue>>
+    + Decodes<bool>
+    + Decodes<i16>
+    + Decodes<i32>
+    + Decodes<i64>
+    + Decodes<f32>
+    + Decodes<f64>
+    + Decodes<String>
+    + Decodes<Uuid>
+    + Decodes<DateTime<Utc>>
+    + Decodes<NaiveDateTime>
+    + Decodes<NaiveDate>
+{
+    /// The dialect that PRQL is compiled into.
+    fn dialect() -> Dialect;
+
+    /// Rewrites the `$n` placeholders emitted by the driver into the syntax
+    /// that this backend understands.
+    fn placeholders(sql: String) -> String {
+        sql
+    }
+
+    /// The name of the type of the named column of a row, as reported by the
+    /// database (e.g. `INT8` or `TEXT`).
+    fn type_name(row: &Self::Row, column: &str) -> sqlx::Result<String>;
+
+    /// Whether the named column of a row is NULL.
+    fn is_null(row: &Self::Row, column: &str) -> sqlx::Result<bool>;
+
+    /// Whether NULLs sort as if they were larger than any other value, i.e.
+    /// they come last in ascending order and first in descending order.
+    fn nulls_are_largest() -> bool;
+
+    /// The largest number of arguments that can be bound to one statement.
+    fn max_binds() -> usize;
+
+    /// The number of rows that were inserted, updated or deleted by a
+    /// statement.
+    fn rows_affected(result: &Self::QueryResult) -> u64;
+
+    /// Wraps a query into a statement that estimates how many rows the query
+    /// selects without running it, if the backend can.
+    fn explain(_sql: &str) -> Option<String> {
+        None
+    }
+
+    /// Reads the number of rows from the row selected by `explain`.
+    fn estimated_rows(_row: &Self::Row) -> sqlx::Result<i64> {
+        Err(sqlx::Error::Protocol(
+            "backend cannot estimate rows".to_string(),
+        ))
+    }
+}
+
+/// A database whose query arguments can be collected before the query is
+/// executed.
+pub trait BackendArguments: Database {
+    type Args<'q>: sqlx::Arguments<'q, Database = Self> + IntoArguments<'q, Self>;
+
+    /// Shortens the lifetime of the arguments collected by the driver so that
+    /// they can be executed alongside a borrowed SQL string.
+    fn args<'q>(arguments: Self::Args<'static>) -> Self::Args<'q>;
+}
+
+impl Backend for Postgres {
+    fn dialect() -> Dialect {
+        Dialect::Postgres
+    }
+
+    fn type_name(row: &Self::Row, column: &str) -> sqlx::Result<String> {
+        Ok(row.try_get_raw(column)?.type_info().name().to_string())
+    }
+
+    fn is_null(row: &Self::Row, column: &str) -> sqlx::Result<bool> {
+        Ok(row.try_get_raw(column)?.is_null())
+    }
+
+    fn nulls_are_largest() -> bool {
+        true
+    }
+
+    fn max_binds() -> usize {
+        // the number of arguments is sent as an unsigned 16-bit integer
+        u16::MAX as usize
+    }
+
+    fn rows_affected(result: &Self::QueryResult) -> u64 {
+        result.rows_affected()
+    }
+
+    fn explain(sql: &str) -> Option<String> {
+        Some(format!("EXPLAIN (FORMAT JSON) {}", sql))
+    }
+
+    fn estimated_rows(row: &Self::Row) -> sqlx::Result<i64> {
+        let Json(plan) = row.try_get::<Json<JsonValue>, _>("QUERY PLAN")?;
+        plan_rows(&plan).ok_or_else(|| {
+            sqlx::Error::Protocol(format!("query plan has no row estimate: {}", plan))
+        })
+    }
+}
+
+/// Reads the number of rows that the planner estimates for the top node of a
+/// Postgres query plan in the JSON format.
+fn plan_rows(plan: &JsonValue) -> Option<i64> {
+    let rows = plan.get(0)?.get("Plan")?.get("Plan Rows")?.as_f64()?;
+    Some(rows.round() as i64)
+}
+
+impl BackendArguments for Postgres {
+    type Args<'q> = PgArguments;
+
+    fn args<'q>(arguments: Self::Args<'static>) -> Self::Args<'q> {
+        arguments
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl Backend for Sqlite {
+    fn dialect() -> Dialect {
+        Dialect::SQLite
+    }
+
+    fn placeholders(sql: String) -> String {
+        // SQLite treats `$1` as a named parameter and numbers named parameters
+        // in order of appearance, but PRQL is free to reorder expressions. The
+        // `?1` form always refers to the n-th argument.
+        let mut rewritten = String::with_capacity(sql.len());
+        let mut quoted = false;
+        let mut chars = sql.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\'' => quoted = !quoted,
+                '$' if !quoted && chars.peek().is_some_and(char::is_ascii_digit) => {
+                    rewritten.push('?');
+                    continue;
+                }
+                _ => {}
+            }
+            rewritten.push(c);
+        }
+        rewritten
+    }
+
+    fn type_name(row: &Self::Row, column: &str) -> sqlx::Result<String> {
+        Ok(row.try_get_raw(column)?.type_info().name().to_string())
+    }
+
+    fn is_null(row: &Self::Row, column: &str) -> sqlx::Result<bool> {
+        Ok(row.try_get_raw(column)?.is_null())
+    }
+
+    fn nulls_are_largest() -> bool {
+        false
+    }
+
+    fn max_binds() -> usize {
+        // SQLITE_MAX_VARIABLE_NUMBER since SQLite 3.32
+        32766
+    }
+
+    fn rows_affected(result: &Self::QueryResult) -> u64 {
+        result.rows_affected()
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl BackendArguments for Sqlite {
+    type Args<'q> = SqliteArguments<'q>;
+
+    fn args<'q>(arguments: Self::Args<'static>) -> Self::Args<'q> {
+        arguments
+    }
+}
+
+/// A backend that can bind values of type `T` as query arguments.
+pub trait Encodes<T>: BackendArguments {
+    fn add_argument(arguments: &mut Self::Args<'static>, value: T);
+}
+
+// Postgres encodes arguments as soon as they are bound, so borrowed values can
+// be bound as well.
+impl<T> Encodes<T> for Postgres
+where
+    T: Send + Type<Postgres> + for<'q> Encode<'q, Postgres>,
+{
+    fn add_argument(arguments: &mut PgArguments, value: T) {
+        sqlx::Arguments::add(arguments, value);
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<T> Encodes<T> for Sqlite
+where
+    T: 'static + Send + Type<Sqlite> + Encode<'static, Sqlite>,
+{
+    fn add_argument(arguments: &mut SqliteArguments<'static>, value: T) {
+        sqlx::Arguments::add(arguments, value);
+    }
+}
+
+/// A backend that can decode values of type `T` from its rows.
+pub trait Decodes<T>: Database {
+    fn decode_column(row: &Self::Row, column: &str) -> sqlx::Result<T>;
+}
+
+impl<DB, T> Decodes<T> for DB
+where
+    DB: Database,
+    for<'r> T: Decode<'r, DB> + Type<DB>,
+    for<'a> &'a str: ColumnIndex<DB::Row>,
+{
+    fn decode_column(row: &Self::Row, column: &str) -> sqlx::Result<T> {
+        row.try_get(column)
+    }
+}
+
+/// Decodes the named column of a row.
+pub fn try_get<DB, T>(row: &DB::Row, column: &str) -> sqlx::Result<T>
+where
+    DB: Decodes<T>,
+{
+    DB::decode_column(row, column)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_plan_rows() {
+        let plan = serde_json::json!([{
+            "Plan": {
+                "Node Type": "Seq Scan",
+                "Relation Name": "users",
+                "Startup Cost": 0.0,
+                "Total Cost": 1.0,
+                "Plan Rows": 49_512_880,
+                "Plan Width": 4
+            }
+        }]);
+        assert_eq!(plan_rows(&plan), Some(49_512_880));
+        assert_eq!(plan_rows(&serde_json::json!([])), None);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_placeholders() {
+        assert_eq!(
+            <Sqlite as Backend>::placeholders(
+                "SELECT *, $2 AS b FROM t WHERE a > $1 AND c = '$3' AND d = $10".to_string()
+            ),
+            "SELECT *, ?2 AS b FROM t WHERE a > ?1 AND c = '$3' AND d = ?10"
+        );
+    }
+}
@@ -3,7 +3,6 @@ use std::{
     sync::{Arc, RwLock},
 };
 
-
 pub trait Cache {
     fn get(&self, key: &str) -> Option<String>;
     fn insert(&self, key: String, value: String);
@@ -87,4 +86,26 @@ mod test {
         assert!(cached_duration < duration);
         println!("cached_duration: {:?}", cached_duration);
     }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_cache_per_dialect() {
+        let cache = FifoCache::new(10);
+
+        let mut driver = Driver::with_cache(Box::new(cache.clone()));
+        {
+            from(table("users"))
+                .filter(gt(col("age"), 18))
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(driver.sql(), "SELECT * FROM users WHERE age > $1");
+
+        let mut driver = Driver::<sqlx::Sqlite>::empty_with_cache(Box::new(cache.clone()));
+        {
+            from(table("users"))
+                .filter(gt(col("age"), 18))
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(driver.sql(), "SELECT * FROM users WHERE age > ?1");
+    }
 }
@@ -1,5 +1,6 @@
 use crate::{
-    cond::{Eq, Gt, Lt},
+    backend::Backend,
+    cond::{in_, Between, Eq, Ge, Gt, In, IsNull, Le, Lt, Ne},
     driver::{Driver, PushPrql},
     sort::{Order, Sort},
 };
@@ -36,17 +37,41 @@ impl ColumnName {
         Eq { lhs: self, rhs }
     }
 
+    pub fn ne<RHS>(&self, rhs: RHS) -> Ne<&Self, RHS> {
+        Ne { lhs: self, rhs }
+    }
+
     pub fn gt<RHS>(&self, rhs: RHS) -> Gt<&Self, RHS> {
         Gt { lhs: self, rhs }
     }
 
+    pub fn ge<RHS>(&self, rhs: RHS) -> Ge<&Self, RHS> {
+        Ge { lhs: self, rhs }
+    }
+
     pub fn lt<RHS>(&self, rhs: RHS) -> Lt<&Self, RHS> {
         Lt { lhs: self, rhs }
     }
+
+    pub fn le<RHS>(&self, rhs: RHS) -> Le<&Self, RHS> {
+        Le { lhs: self, rhs }
+    }
+
+    pub fn is_null(&self) -> IsNull<&Self> {
+        IsNull { expr: self }
+    }
+
+    pub fn between<Lo, Hi>(&self, lo: Lo, hi: Hi) -> Between<&Self, Lo, Hi> {
+        Between { expr: self, lo, hi }
+    }
+
+    pub fn in_<RHS>(&self, values: impl IntoIterator<Item = RHS>) -> In<&Self, RHS> {
+        in_(self, values)
+    }
 }
 
-impl PushPrql for ColumnName {
-    fn push_to_driver(&self, driver: &mut Driver) {
+impl<DB: Backend> PushPrql<DB> for ColumnName {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         driver.push(self.name);
     }
 }
@@ -111,20 +136,44 @@ impl<Col> JsonAccessor<Col> {
         Eq { lhs: self, rhs }
     }
 
+    pub fn ne<RHS>(&self, rhs: RHS) -> Ne<&Self, RHS> {
+        Ne { lhs: self, rhs }
+    }
+
     pub fn gt<RHS>(&self, rhs: RHS) -> Gt<&Self, RHS> {
         Gt { lhs: self, rhs }
     }
 
+    pub fn ge<RHS>(&self, rhs: RHS) -> Ge<&Self, RHS> {
+        Ge { lhs: self, rhs }
+    }
+
     pub fn lt<RHS>(&self, rhs: RHS) -> Lt<&Self, RHS> {
         Lt { lhs: self, rhs }
     }
+
+    pub fn le<RHS>(&self, rhs: RHS) -> Le<&Self, RHS> {
+        Le { lhs: self, rhs }
+    }
+
+    pub fn is_null(&self) -> IsNull<&Self> {
+        IsNull { expr: self }
+    }
+
+    pub fn between<Lo, Hi>(&self, lo: Lo, hi: Hi) -> Between<&Self, Lo, Hi> {
+        Between { expr: self, lo, hi }
+    }
+
+    pub fn in_<RHS>(&self, values: impl IntoIterator<Item = RHS>) -> In<&Self, RHS> {
+        in_(self, values)
+    }
 }
 
-impl<Col> PushPrql for JsonAccessor<Col>
+impl<DB: Backend, Col> PushPrql<DB> for JsonAccessor<Col>
 where
-    Col: PushPrql,
+    Col: PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut Driver) {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         driver.push("s\"");
         self.col.push_to_driver(driver);
         match self.op {
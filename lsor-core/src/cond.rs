@@ -1,32 +1,70 @@
-use crate::driver::{Driver, PushPrql};
+use crate::{
+    backend::Backend,
+    driver::{Driver, PushPrql},
+};
 
 pub fn and<LHS, RHS>(lhs: LHS, rhs: RHS) -> And<LHS, RHS> {
     And { lhs, rhs }
 }
 
+pub fn between<Expr, Lo, Hi>(expr: Expr, lo: Lo, hi: Hi) -> Between<Expr, Lo, Hi> {
+    Between { expr, lo, hi }
+}
+
 pub fn eq<LHS, RHS>(lhs: LHS, rhs: RHS) -> Eq<LHS, RHS> {
     Eq { lhs, rhs }
 }
 
+pub fn ge<LHS, RHS>(lhs: LHS, rhs: RHS) -> Ge<LHS, RHS> {
+    Ge { lhs, rhs }
+}
+
 pub fn gt<LHS, RHS>(lhs: LHS, rhs: RHS) -> Gt<LHS, RHS> {
     Gt { lhs, rhs }
 }
 
+pub fn in_<LHS, RHS>(lhs: LHS, values: impl IntoIterator<Item = RHS>) -> In<LHS, RHS> {
+    In {
+        lhs,
+        values: values.into_iter().collect(),
+    }
+}
+
+pub fn is_null<Expr>(expr: Expr) -> IsNull<Expr> {
+    IsNull { expr }
+}
+
+pub fn le<LHS, RHS>(lhs: LHS, rhs: RHS) -> Le<LHS, RHS> {
+    Le { lhs, rhs }
+}
+
 pub fn lt<LHS, RHS>(lhs: LHS, rhs: RHS) -> Lt<LHS, RHS> {
     Lt { lhs, rhs }
 }
 
+pub fn ne<LHS, RHS>(lhs: LHS, rhs: RHS) -> Ne<LHS, RHS> {
+    Ne { lhs, rhs }
+}
+
+pub fn not<Cond>(cond: Cond) -> Not<Cond> {
+    Not { cond }
+}
+
+pub fn or<LHS, RHS>(lhs: LHS, rhs: RHS) -> Or<LHS, RHS> {
+    Or { lhs, rhs }
+}
+
 pub struct And<LHS, RHS> {
     pub lhs: LHS,
     pub rhs: RHS,
 }
 
-impl<LHS, RHS> PushPrql for And<LHS, RHS>
+impl<DB: Backend, LHS, RHS> PushPrql<DB> for And<LHS, RHS>
 where
-    LHS: PushPrql,
-    RHS: PushPrql,
+    LHS: PushPrql<DB>,
+    RHS: PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut Driver) {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         driver.push('(');
         self.lhs.push_to_driver(driver);
         driver.push(") && (");
@@ -35,17 +73,48 @@ where
     }
 }
 
+/// Whether `expr` is in the inclusive range from `lo` to `hi`.
+pub struct Between<Expr, Lo, Hi> {
+    pub expr: Expr,
+    pub lo: Lo,
+    pub hi: Hi,
+}
+
+impl<DB: Backend, Expr, Lo, Hi> PushPrql<DB> for Between<Expr, Lo, Hi>
+where
+    Expr: PushPrql<DB>,
+    Lo: PushPrql<DB>,
+    Hi: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        // PRQL ranges cannot have parameters as bounds, so the expression is
+        // compared to each bound. It is only pushed once, and its text is
+        // repeated, so that its parameters are not bound twice.
+        driver.push('(');
+        let start = driver.prql().len();
+        self.expr.push_to_driver(driver);
+        let expr = driver.prql()[start..].to_string();
+        driver.push(") >= (");
+        self.lo.push_to_driver(driver);
+        driver.push(") && (");
+        driver.push(expr);
+        driver.push(") <= (");
+        self.hi.push_to_driver(driver);
+        driver.push(')');
+    }
+}
+
 pub struct Eq<LHS, RHS> {
     pub lhs: LHS,
     pub rhs: RHS,
 }
 
-impl<LHS, RHS> PushPrql for Eq<LHS, RHS>
+impl<DB: Backend, LHS, RHS> PushPrql<DB> for Eq<LHS, RHS>
 where
-    LHS: PushPrql,
-    RHS: PushPrql,
+    LHS: PushPrql<DB>,
+    RHS: PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut Driver) {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         driver.push('(');
         self.lhs.push_to_driver(driver);
         driver.push(") == (");
@@ -54,17 +123,36 @@ where
     }
 }
 
+pub struct Ge<LHS, RHS> {
+    pub lhs: LHS,
+    pub rhs: RHS,
+}
+
+impl<DB: Backend, LHS, RHS> PushPrql<DB> for Ge<LHS, RHS>
+where
+    LHS: PushPrql<DB>,
+    RHS: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        driver.push('(');
+        self.lhs.push_to_driver(driver);
+        driver.push(") >= (");
+        self.rhs.push_to_driver(driver);
+        driver.push(')');
+    }
+}
+
 pub struct Gt<LHS, RHS> {
     pub lhs: LHS,
     pub rhs: RHS,
 }
 
-impl<LHS, RHS> PushPrql for Gt<LHS, RHS>
+impl<DB: Backend, LHS, RHS> PushPrql<DB> for Gt<LHS, RHS>
 where
-    LHS: PushPrql,
-    RHS: PushPrql,
+    LHS: PushPrql<DB>,
+    RHS: PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut Driver) {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         driver.push('(');
         self.lhs.push_to_driver(driver);
         driver.push(") > (");
@@ -73,17 +161,77 @@ where
     }
 }
 
+/// Whether `lhs` is equal to any of the values. It is false when there are
+/// no values.
+pub struct In<LHS, RHS> {
+    pub lhs: LHS,
+    pub values: Vec<RHS>,
+}
+
+impl<DB: Backend, LHS, RHS> PushPrql<DB> for In<LHS, RHS>
+where
+    LHS: PushPrql<DB>,
+    RHS: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        driver.push("((");
+        self.lhs.push_to_driver(driver);
+        driver.push(") | in [");
+        for (i, value) in self.values.iter().enumerate() {
+            if i > 0 {
+                driver.push(", ");
+            }
+            value.push_to_driver(driver);
+        }
+        driver.push("])");
+    }
+}
+
+pub struct IsNull<Expr> {
+    pub expr: Expr,
+}
+
+impl<DB: Backend, Expr> PushPrql<DB> for IsNull<Expr>
+where
+    Expr: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        driver.push('(');
+        self.expr.push_to_driver(driver);
+        driver.push(") == null");
+    }
+}
+
+pub struct Le<LHS, RHS> {
+    pub lhs: LHS,
+    pub rhs: RHS,
+}
+
+impl<DB: Backend, LHS, RHS> PushPrql<DB> for Le<LHS, RHS>
+where
+    LHS: PushPrql<DB>,
+    RHS: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        driver.push('(');
+        self.lhs.push_to_driver(driver);
+        driver.push(") <= (");
+        self.rhs.push_to_driver(driver);
+        driver.push(')');
+    }
+}
+
 pub struct Lt<LHS, RHS> {
     pub lhs: LHS,
     pub rhs: RHS,
 }
 
-impl<LHS, RHS> PushPrql for Lt<LHS, RHS>
+impl<DB: Backend, LHS, RHS> PushPrql<DB> for Lt<LHS, RHS>
 where
-    LHS: PushPrql,
-    RHS: PushPrql,
+    LHS: PushPrql<DB>,
+    RHS: PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut Driver) {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         driver.push('(');
         self.lhs.push_to_driver(driver);
         driver.push(") < (");
@@ -91,3 +239,203 @@ where
         driver.push(')');
     }
 }
+
+pub struct Ne<LHS, RHS> {
+    pub lhs: LHS,
+    pub rhs: RHS,
+}
+
+impl<DB: Backend, LHS, RHS> PushPrql<DB> for Ne<LHS, RHS>
+where
+    LHS: PushPrql<DB>,
+    RHS: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        driver.push('(');
+        self.lhs.push_to_driver(driver);
+        driver.push(") != (");
+        self.rhs.push_to_driver(driver);
+        driver.push(')');
+    }
+}
+
+pub struct Not<Cond> {
+    pub cond: Cond,
+}
+
+impl<DB: Backend, Cond> PushPrql<DB> for Not<Cond>
+where
+    Cond: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        driver.push("!(");
+        self.cond.push_to_driver(driver);
+        driver.push(')');
+    }
+}
+
+pub struct Or<LHS, RHS> {
+    pub lhs: LHS,
+    pub rhs: RHS,
+}
+
+impl<DB: Backend, LHS, RHS> PushPrql<DB> for Or<LHS, RHS>
+where
+    LHS: PushPrql<DB>,
+    RHS: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        driver.push('(');
+        self.lhs.push_to_driver(driver);
+        driver.push(") || (");
+        self.rhs.push_to_driver(driver);
+        driver.push(')');
+    }
+}
+
+/// Lets conditions be combined with `&`, `|` and `!`.
+macro_rules! impl_ops {
+    ($t:ident<$($g:ident),*>) => {
+        impl<$($g,)* Rhs> ::std::ops::BitAnd<Rhs> for $t<$($g),*> {
+            type Output = $crate::cond::And<Self, Rhs>;
+
+            fn bitand(self, rhs: Rhs) -> Self::Output {
+                $crate::cond::And { lhs: self, rhs }
+            }
+        }
+
+        impl<$($g,)* Rhs> ::std::ops::BitOr<Rhs> for $t<$($g),*> {
+            type Output = $crate::cond::Or<Self, Rhs>;
+
+            fn bitor(self, rhs: Rhs) -> Self::Output {
+                $crate::cond::Or { lhs: self, rhs }
+            }
+        }
+
+        impl<$($g),*> ::std::ops::Not for $t<$($g),*> {
+            type Output = $crate::cond::Not<Self>;
+
+            fn not(self) -> Self::Output {
+                $crate::cond::Not { cond: self }
+            }
+        }
+    };
+}
+
+pub(crate) use impl_ops;
+
+impl_ops!(And<LHS, RHS>);
+impl_ops!(Between<Expr, Lo, Hi>);
+impl_ops!(Eq<LHS, RHS>);
+impl_ops!(Ge<LHS, RHS>);
+impl_ops!(Gt<LHS, RHS>);
+impl_ops!(In<LHS, RHS>);
+impl_ops!(IsNull<Expr>);
+impl_ops!(Le<LHS, RHS>);
+impl_ops!(Lt<LHS, RHS>);
+impl_ops!(Ne<LHS, RHS>);
+impl_ops!(Not<Cond>);
+impl_ops!(Or<LHS, RHS>);
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        column::{col, json},
+        expr::add,
+        from::from,
+        table::{dot, table},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_combinators() {
+        let mut driver = Driver::new();
+        {
+            from(table("users"))
+                .filter(or(
+                    and(ne(col("role"), "admin"), not(is_null(col("email")))),
+                    and(ge(col("age"), 18), le(col("age"), 65)),
+                ))
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT * FROM users WHERE role <> $1 AND NOT email IS NULL OR age >= $2 AND age <= $3"
+        );
+    }
+
+    #[test]
+    fn test_between_and_in() {
+        let mut driver = Driver::new();
+        {
+            from(table("users"))
+                .filter(and(
+                    between(col("age"), 18, 65),
+                    in_(col("role"), ["admin", "owner"]),
+                ))
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT * FROM users WHERE age >= $1 AND age <= $2 AND role IN ($3, $4)"
+        );
+
+        let mut driver = Driver::new();
+        {
+            from(table("users"))
+                .filter(in_(col("role"), Vec::<&str>::new()))
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(driver.sql(), "SELECT * FROM users WHERE false");
+
+        let mut driver = Driver::new();
+        {
+            from(table("users"))
+                .filter(between(add(col("age"), 1), 18, 65))
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT * FROM users WHERE age + $1 >= $2 AND age + $1 <= $3"
+        );
+        assert_eq!(driver.binds(), 3);
+    }
+
+    #[test]
+    fn test_methods() {
+        let mut driver = Driver::new();
+        {
+            let (age, config, email) = (
+                col("age"),
+                json(col("config")).get("theme"),
+                dot(table("users"), col("email")),
+            );
+            from(table("users"))
+                .filter(and(
+                    and(age.between(18, 65), config.in_(["dark"])),
+                    and(email.is_null(), email.ne("a@lsor.dev")),
+                ))
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT * FROM users WHERE age >= $1 AND age <= $2 AND config->'theme' IN ($3) AND email IS NULL AND email <> $4"
+        );
+    }
+
+    #[test]
+    fn test_operators() {
+        let mut driver = Driver::new();
+        {
+            let (age, email) = (col("age"), col("email"));
+            from(table("users"))
+                .filter((age.ge(18) & !email.is_null()) | age.lt(0))
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT * FROM users WHERE age >= $1 AND NOT email IS NULL OR age < $2"
+        );
+    }
+}
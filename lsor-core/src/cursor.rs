@@ -1,12 +1,18 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, fmt};
 
 use base64::Engine;
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, Datelike as _, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 use sqlx::{database::HasValueRef, Decode, Postgres, TypeInfo, ValueRef as _};
 use uuid::Uuid;
 
-use crate::var::Var;
+use crate::{
+    backend::{try_get, Backend},
+    error::CursorError,
+    var::Var,
+};
 
 pub trait Iterable {
     fn cursor(&self) -> Cursor;
@@ -107,6 +113,75 @@ impl Cursor {
         })
     }
 
+    /// Infers the cursor from the name of a database type, as reported by
+    /// `Backend::type_name`.
+    pub fn from_type_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "INT4" | "INT" => Self::I32,
+            // SQLite integers are always 64 bits wide
+            "INT8" | "BIGINT" | "INTEGER" => Self::I64,
+            "FLOAT4" => Self::F32,
+            // SQLite reals are always 64 bits wide
+            "FLOAT8" | "DOUBLE PRECISION" | "REAL" => Self::F64,
+            "TEXT" | "VARCHAR" | "BPCHAR" | "NAME" => Self::String,
+            "UUID" => Self::Uuid,
+            "TIMESTAMPTZ" | "DATETIME" => Self::DateTime,
+            _ => return None,
+        })
+    }
+
+    /// Decodes the named column of a row as the type of this cursor and
+    /// encodes it. Unlike `infer`, this works for every backend.
+    pub fn encode_column<DB: Backend>(&self, row: &DB::Row, column: &str) -> sqlx::Result<String> {
+        match self.decode_column::<DB>(row, column)? {
+            Var::Null => Err(sqlx::Error::Decode(
+                format!("cursor column '{}' is null", column).into(),
+            )),
+            value => Ok(Self::encode(&value)),
+        }
+    }
+
+    /// Decodes the named column of a row as the type of this cursor, or as
+    /// `Var::Null` if the column is NULL.
+    pub fn decode_column<DB: Backend>(&self, row: &DB::Row, column: &str) -> sqlx::Result<Var> {
+        if DB::is_null(row, column)? {
+            return Ok(Var::Null);
+        }
+        Ok(match self {
+            Self::I32 => Var::I32(try_get::<DB, i32>(row, column)?),
+            Self::I64 => Var::I64(try_get::<DB, i64>(row, column)?),
+            Self::F32 => Var::F32(try_get::<DB, f32>(row, column)?),
+            Self::F64 => Var::F64(try_get::<DB, f64>(row, column)?),
+            Self::String => Var::String(try_get::<DB, String>(row, column)?),
+            Self::Uuid => Var::Uuid(try_get::<DB, Uuid>(row, column)?),
+            Self::DateTime => Var::DateTime(try_get::<DB, DateTime<Utc>>(row, column)?),
+        })
+    }
+
+    /// Decodes the named column of a row as the cursor inferred from the type
+    /// of the column.
+    pub fn infer_column<DB: Backend>(row: &DB::Row, column: &str) -> sqlx::Result<Var> {
+        if DB::is_null(row, column)? {
+            return Ok(Var::Null);
+        }
+        let name = DB::type_name(row, column)?;
+        if let Some(cursor) = Self::from_type_name(&name) {
+            return cursor.decode_column::<DB>(row, column);
+        }
+        // types that no sortable field has, but that columns can be sorted by
+        Ok(match name.as_str() {
+            "BOOL" | "BOOLEAN" => Var::Bool(try_get::<DB, bool>(row, column)?),
+            "INT2" | "SMALLINT" => Var::I32(try_get::<DB, i16>(row, column)?.into()),
+            "TIMESTAMP" => Var::NaiveDateTime(try_get::<DB, NaiveDateTime>(row, column)?),
+            "DATE" => Var::Date(try_get::<DB, NaiveDate>(row, column)?),
+            _ => {
+                return Err(sqlx::Error::Decode(
+                    format!("invalid cursor type during inference: {}", name).into(),
+                ))
+            }
+        })
+    }
+
     pub fn decode(&self, encoded: &str) -> Var {
         match self {
             Self::I32 => Var::I32(I32Cursor::decode(encoded)),
@@ -121,6 +196,7 @@ impl Cursor {
 
     pub fn encode(literal: &Var) -> String {
         match literal {
+            Var::Null => panic!("invalid cursor type: null"),
             Var::Bool(_) => panic!("invalid cursor type: bool"),
             Var::I32(v) => I32Cursor::encode(v),
             Var::I64(v) => I64Cursor::encode(v),
@@ -129,6 +205,8 @@ impl Cursor {
             Var::String(v) => StringCursor::encode(v),
             Var::Uuid(v) => UuidCursor::encode(v),
             Var::DateTime(v) => DateTimeCursor::encode(v),
+            Var::NaiveDateTime(v) => DateTimeCursor::encode(&v.and_utc()),
+            Var::Date(v) => DateTimeCursor::encode(&v.and_time(NaiveTime::MIN).and_utc()),
         }
     }
 
@@ -147,30 +225,6 @@ impl Cursor {
         }
         Ordering::Less
     }
-
-    pub fn min(self) -> Var {
-        match self {
-            Self::I32 => Var::I32(I32Cursor::min()),
-            Self::I64 => Var::I64(I64Cursor::min()),
-            Self::F32 => Var::F32(F32Cursor::min()),
-            Self::F64 => Var::F64(F64Cursor::min()),
-            Self::String => Var::String(StringCursor::min()),
-            Self::Uuid => Var::Uuid(UuidCursor::min()),
-            Self::DateTime => Var::DateTime(DateTimeCursor::min()),
-        }
-    }
-
-    pub fn max(self) -> Var {
-        match self {
-            Self::I32 => Var::I32(I32Cursor::max()),
-            Self::I64 => Var::I64(I64Cursor::max()),
-            Self::F32 => Var::F32(F32Cursor::max()),
-            Self::F64 => Var::F64(F64Cursor::max()),
-            Self::String => Var::String(StringCursor::max()),
-            Self::Uuid => Var::Uuid(UuidCursor::max()),
-            Self::DateTime => Var::DateTime(DateTimeCursor::max()),
-        }
-    }
 }
 
 impl From<I32Cursor> for Cursor {
@@ -203,6 +257,211 @@ impl From<DateTimeCursor> for Cursor {
     }
 }
 
+/// The version of the format that composite cursors are encoded with. It is
+/// the first byte of every encoded composite cursor, so that the format can
+/// change without misinterpreting cursors handed out by older versions.
+pub const COMPOSITE_CURSOR_VERSION: u8 = 2;
+
+/// A cursor made up of one value for every key of a sorting, e.g.
+/// `(created_at, id)`. Values are tagged with their type, so decoding does not
+/// need to know the types of the keys up front.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompositeCursor {
+    pub values: Vec<Var>,
+}
+
+impl CompositeCursor {
+    pub fn new(values: Vec<Var>) -> Self {
+        Self { values }
+    }
+
+    pub fn decode(encoded: &str) -> Result<Self, CursorError> {
+        let buf = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|_| CursorError::Malformed {
+                cursor: encoded.to_string(),
+            })?;
+        Self::from_bytes(encoded, &buf)
+    }
+
+    pub fn encode(&self) -> String {
+        base64::engine::general_purpose::STANDARD.encode(self.to_bytes())
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![COMPOSITE_CURSOR_VERSION];
+        for value in &self.values {
+            match value {
+                Var::Null => buf.push(b'n'),
+                Var::Bool(v) => {
+                    buf.push(b'b');
+                    buf.push(*v as u8);
+                }
+                Var::I32(v) => {
+                    buf.push(b'i');
+                    buf.extend(v.to_be_bytes());
+                }
+                Var::I64(v) => {
+                    buf.push(b'l');
+                    buf.extend(v.to_be_bytes());
+                }
+                Var::F32(v) => {
+                    buf.push(b'f');
+                    buf.extend(v.to_be_bytes());
+                }
+                Var::F64(v) => {
+                    buf.push(b'd');
+                    buf.extend(v.to_be_bytes());
+                }
+                Var::String(v) => {
+                    buf.push(b's');
+                    buf.extend((v.len() as u32).to_be_bytes());
+                    buf.extend(v.as_bytes());
+                }
+                Var::Uuid(v) => {
+                    buf.push(b'u');
+                    buf.extend(v.as_bytes());
+                }
+                Var::DateTime(v) => {
+                    // nanoseconds since the epoch only fit in an i64 between
+                    // the years 1677 and 2262
+                    buf.push(b't');
+                    buf.extend(v.timestamp().to_be_bytes());
+                    buf.extend(v.timestamp_subsec_nanos().to_be_bytes());
+                }
+                Var::NaiveDateTime(v) => {
+                    buf.push(b'T');
+                    buf.extend(v.and_utc().timestamp().to_be_bytes());
+                    buf.extend(v.and_utc().timestamp_subsec_nanos().to_be_bytes());
+                }
+                Var::Date(v) => {
+                    buf.push(b'D');
+                    buf.extend(v.num_days_from_ce().to_be_bytes());
+                }
+            }
+        }
+        buf
+    }
+
+    fn from_bytes(encoded: &str, buf: &[u8]) -> Result<Self, CursorError> {
+        let malformed = || CursorError::Malformed {
+            cursor: encoded.to_string(),
+        };
+        let (&version, buf) = buf.split_first().ok_or_else(malformed)?;
+        if version != COMPOSITE_CURSOR_VERSION {
+            return Err(CursorError::UnsupportedVersion {
+                cursor: encoded.to_string(),
+                version,
+            });
+        }
+        let values = Self::values_from_bytes(buf).ok_or_else(malformed)?;
+        Ok(Self { values })
+    }
+
+    fn values_from_bytes(mut buf: &[u8]) -> Option<Vec<Var>> {
+        fn take<'a>(buf: &mut &'a [u8], n: usize) -> Option<&'a [u8]> {
+            if buf.len() < n {
+                return None;
+            }
+            let (head, tail) = buf.split_at(n);
+            *buf = tail;
+            Some(head)
+        }
+
+        fn take_array<const N: usize>(buf: &mut &[u8]) -> Option<[u8; N]> {
+            take(buf, N)?.try_into().ok()
+        }
+
+        let mut values = Vec::new();
+        while let Some((&tag, tail)) = buf.split_first() {
+            buf = tail;
+            values.push(match tag {
+                b'n' => Var::Null,
+                b'b' => Var::Bool(take_array::<1>(&mut buf)?[0] != 0),
+                b'i' => Var::I32(i32::from_be_bytes(take_array(&mut buf)?)),
+                b'l' => Var::I64(i64::from_be_bytes(take_array(&mut buf)?)),
+                b'f' => Var::F32(f32::from_be_bytes(take_array(&mut buf)?)),
+                b'd' => Var::F64(f64::from_be_bytes(take_array(&mut buf)?)),
+                b's' => {
+                    let len = u32::from_be_bytes(take_array(&mut buf)?) as usize;
+                    Var::String(String::from_utf8(take(&mut buf, len)?.to_vec()).ok()?)
+                }
+                b'u' => Var::Uuid(Uuid::from_bytes(take_array(&mut buf)?)),
+                b't' => Var::DateTime(DateTime::from_timestamp(
+                    i64::from_be_bytes(take_array(&mut buf)?),
+                    u32::from_be_bytes(take_array(&mut buf)?),
+                )?),
+                b'T' => Var::NaiveDateTime(
+                    DateTime::from_timestamp(
+                        i64::from_be_bytes(take_array(&mut buf)?),
+                        u32::from_be_bytes(take_array(&mut buf)?),
+                    )?
+                    .naive_utc(),
+                ),
+                b'D' => Var::Date(NaiveDate::from_num_days_from_ce_opt(i32::from_be_bytes(
+                    take_array(&mut buf)?,
+                ))?),
+                _ => return None,
+            });
+        }
+        Some(values)
+    }
+}
+
+/// Signs composite cursors with HMAC-SHA256 using a server secret, so that
+/// clients cannot craft cursors of their own.
+#[derive(Clone)]
+pub struct CursorSigner {
+    mac: Hmac<Sha256>,
+}
+
+impl CursorSigner {
+    /// The length of the signature that is appended to every cursor.
+    const SIGNATURE_LEN: usize = 32;
+
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            mac: Hmac::new_from_slice(secret.as_ref()).expect("hmac accepts keys of any length"),
+        }
+    }
+
+    pub fn sign(&self, cursor: &CompositeCursor) -> String {
+        let mut buf = cursor.to_bytes();
+        let mut mac = self.mac.clone();
+        mac.update(&buf);
+        buf.extend(mac.finalize().into_bytes());
+        base64::engine::general_purpose::STANDARD.encode(buf)
+    }
+
+    pub fn verify(&self, encoded: &str) -> Result<CompositeCursor, CursorError> {
+        let buf = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|_| CursorError::Malformed {
+                cursor: encoded.to_string(),
+            })?;
+        if buf.len() < Self::SIGNATURE_LEN {
+            return Err(CursorError::Malformed {
+                cursor: encoded.to_string(),
+            });
+        }
+        let (buf, signature) = buf.split_at(buf.len() - Self::SIGNATURE_LEN);
+        let mut mac = self.mac.clone();
+        mac.update(buf);
+        mac.verify_slice(signature)
+            .map_err(|_| CursorError::Forged {
+                cursor: encoded.to_string(),
+            })?;
+        CompositeCursor::from_bytes(encoded, buf)
+    }
+}
+
+impl fmt::Debug for CursorSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // never leak the secret into logs
+        f.debug_struct("CursorSigner").finish_non_exhaustive()
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct I32Cursor;
 
@@ -230,10 +489,6 @@ impl I32Cursor {
     pub fn min() -> i32 {
         i32::MIN
     }
-
-    pub fn max() -> i32 {
-        i32::MAX
-    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -263,10 +518,6 @@ impl I64Cursor {
     pub fn min() -> i64 {
         i64::MIN
     }
-
-    pub fn max() -> i64 {
-        i64::MAX
-    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -296,10 +547,6 @@ impl F32Cursor {
     pub fn min() -> f32 {
         f32::MIN
     }
-
-    pub fn max() -> f32 {
-        f32::MAX
-    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -329,10 +576,6 @@ impl F64Cursor {
     pub fn min() -> f64 {
         f64::MIN
     }
-
-    pub fn max() -> f64 {
-        f64::MAX
-    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -361,11 +604,6 @@ impl StringCursor {
     pub fn min() -> String {
         "".to_string()
     }
-
-    #[allow(dead_code)]
-    pub fn max() -> String {
-        "~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~".to_string()
-    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -395,10 +633,6 @@ impl UuidCursor {
     pub fn min() -> Uuid {
         Uuid::from_bytes([0; 16])
     }
-
-    pub fn max() -> Uuid {
-        Uuid::from_bytes([255; 16])
-    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -433,8 +667,129 @@ impl DateTimeCursor {
     pub fn min() -> DateTime<Utc> {
         Utc.timestamp_nanos(i64::MIN)
     }
+}
 
-    pub fn max() -> DateTime<Utc> {
-        Utc.timestamp_nanos(i64::MAX)
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_composite_cursor() {
+        let cursor = CompositeCursor::new(vec![
+            Var::Null,
+            Var::Bool(true),
+            Var::I32(-1),
+            Var::I64(2),
+            Var::F32(3.5),
+            Var::F64(-4.5),
+            Var::String("ünïcödé".to_string()),
+            Var::Uuid(Uuid::from_bytes([7; 16])),
+            Var::DateTime(Utc.timestamp_nanos(1_700_000_000_000_000_001)),
+            Var::NaiveDateTime(Utc.timestamp_nanos(1_700_000_000_000_000_001).naive_utc()),
+            Var::Date(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()),
+        ]);
+        assert_eq!(CompositeCursor::decode(&cursor.encode()), Ok(cursor));
+    }
+
+    #[test]
+    fn test_cursor_encode_naive_datetime_and_date() {
+        let datetime = Utc.with_ymd_and_hms(2024, 2, 29, 12, 30, 0).unwrap();
+        assert_eq!(
+            Cursor::DateTime.decode(&Cursor::encode(&Var::NaiveDateTime(datetime.naive_utc()))),
+            Var::DateTime(datetime)
+        );
+        assert_eq!(
+            Cursor::DateTime.decode(&Cursor::encode(&Var::Date(datetime.date_naive()))),
+            Var::DateTime(Utc.with_ymd_and_hms(2024, 2, 29, 0, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_composite_cursor_datetime_range() {
+        let cursor = CompositeCursor::new(vec![
+            Var::DateTime(Utc.with_ymd_and_hms(1, 1, 1, 0, 0, 0).unwrap()),
+            Var::DateTime(Utc.with_ymd_and_hms(9999, 12, 31, 23, 59, 59).unwrap()),
+        ]);
+        assert_eq!(CompositeCursor::decode(&cursor.encode()), Ok(cursor));
+    }
+
+    #[test]
+    fn test_composite_cursor_is_versioned() {
+        let encoded = CompositeCursor::new(vec![Var::I64(1)]).encode();
+        let mut buf = base64::engine::general_purpose::STANDARD
+            .decode(&encoded)
+            .unwrap();
+        assert_eq!(buf[0], COMPOSITE_CURSOR_VERSION);
+
+        buf[0] += 1;
+        let encoded = base64::engine::general_purpose::STANDARD.encode(buf);
+        assert_eq!(
+            CompositeCursor::decode(&encoded),
+            Err(CursorError::UnsupportedVersion {
+                cursor: encoded.clone(),
+                version: COMPOSITE_CURSOR_VERSION + 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_composite_cursor_is_malformed() {
+        assert!(matches!(
+            CompositeCursor::decode("not base64!"),
+            Err(CursorError::Malformed { .. })
+        ));
+        assert!(matches!(
+            CompositeCursor::decode(""),
+            Err(CursorError::Malformed { .. })
+        ));
+
+        let encoded = CompositeCursor::new(vec![Var::I64(1)]).encode();
+        let buf = base64::engine::general_purpose::STANDARD
+            .decode(&encoded)
+            .unwrap();
+        let truncated = base64::engine::general_purpose::STANDARD.encode(&buf[..buf.len() - 1]);
+        assert!(matches!(
+            CompositeCursor::decode(&truncated),
+            Err(CursorError::Malformed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_signed_cursor() {
+        let signer = CursorSigner::new("secret");
+        let cursor = CompositeCursor::new(vec![Var::String("a".to_string()), Var::I64(1)]);
+        let signed = signer.sign(&cursor);
+        assert_eq!(signer.verify(&signed), Ok(cursor.clone()));
+
+        // unsigned cursors and cursors signed with other secrets are rejected
+        let unsigned = CompositeCursor::new(vec![Var::String("a".repeat(64))]);
+        assert!(matches!(
+            signer.verify(&unsigned.encode()),
+            Err(CursorError::Forged { .. })
+        ));
+        assert!(matches!(
+            signer.verify(&CursorSigner::new("other").sign(&cursor)),
+            Err(CursorError::Forged { .. })
+        ));
+        assert!(matches!(
+            signer.verify("bm9wZQ=="),
+            Err(CursorError::Malformed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_signed_cursor_is_tamper_proof() {
+        let signer = CursorSigner::new("secret");
+        let signed = signer.sign(&CompositeCursor::new(vec![Var::I64(1)]));
+        let mut buf = base64::engine::general_purpose::STANDARD
+            .decode(&signed)
+            .unwrap();
+        // flip the last byte of the value
+        buf[9] ^= 1;
+        let tampered = base64::engine::general_purpose::STANDARD.encode(buf);
+        assert_eq!(
+            signer.verify(&tampered),
+            Err(CursorError::Forged { cursor: tampered })
+        );
     }
 }
@@ -1,9 +1,17 @@
-use crate::{column::ColumnName, driver::PushPrql, sort::Sorted, SortedBy, Sorting};
+use crate::{
+    aggregate::{Aggregate, Group, Grouped},
+    backend::Backend,
+    column::ColumnName,
+    driver::PushPrql,
+    select::{select_row, Select, SelectRow},
+    sort::Sorted,
+    SortedBy, Sorting,
+};
 
-pub fn derive_from<Query>(
+pub fn derive_from<DB: Backend, Query>(
     query: Query,
-    derivations: Vec<(ColumnName, &dyn PushPrql)>,
-) -> Derive<Query, &dyn PushPrql> {
+    derivations: Vec<(ColumnName, &dyn PushPrql<DB>)>,
+) -> Derive<Query, &dyn PushPrql<DB>> {
     Derive { query, derivations }
 }
 
@@ -21,14 +29,44 @@ impl<Query, Expr> Derive<Query, Expr> {
     pub fn sort<Sort>(self, sort: Sort) -> Sorted<Self, Sort> {
         Sorted { query: self, sort }
     }
+
+    pub fn select<Expr2>(self, exprs: impl IntoIterator<Item = Expr2>) -> Select<Self, Expr2> {
+        Select {
+            query: self,
+            exprs: exprs.into_iter().collect(),
+        }
+    }
+
+    pub fn select_row<R>(self) -> SelectRow<Self, R> {
+        select_row(self)
+    }
+
+    pub fn aggregate<Expr2>(self, name: &'static str, expr: Expr2) -> Aggregate<Self, Expr2> {
+        Aggregate {
+            query: self,
+            aggregations: vec![(ColumnName { name }, expr)],
+        }
+    }
+
+    pub fn group<Col, Pipeline>(
+        self,
+        columns: impl IntoIterator<Item = Col>,
+        pipeline: impl FnOnce(Grouped) -> Pipeline,
+    ) -> Group<Self, Col, Pipeline> {
+        Group {
+            query: self,
+            columns: columns.into_iter().collect(),
+            pipeline: pipeline(Grouped {}),
+        }
+    }
 }
 
-impl<Query, Expr> PushPrql for Derive<Query, Expr>
+impl<DB: Backend, Query, Expr> PushPrql<DB> for Derive<Query, Expr>
 where
-    Query: PushPrql,
-    Expr: PushPrql,
+    Query: PushPrql<DB>,
+    Expr: PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut crate::driver::Driver) {
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
         self.query.push_to_driver(driver);
         driver.push("\nderive {");
         for (i, (col, expr)) in self.derivations.iter().enumerate() {
@@ -44,11 +82,11 @@ where
     }
 }
 
-impl<Query, Expr> SortedBy for Derive<Query, Expr>
+impl<DB: Backend, Query, Expr> SortedBy<DB> for Derive<Query, Expr>
 where
-    Query: SortedBy,
+    Query: SortedBy<DB>,
 {
-    fn sorting(&self) -> impl Sorting {
+    fn sorting(&self) -> impl Sorting<DB> {
         self.query.sorting()
     }
 }
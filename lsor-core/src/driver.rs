@@ -1,71 +1,126 @@
-use std::fmt::Display;
+use std::{fmt::Display, ops::Range};
 
 use chrono::{DateTime, Utc};
-use sqlx::{postgres::{PgArguments, PgHasArrayType}, Database, Encode, Executor, Postgres, Type};
+use futures_util::{Stream, TryStreamExt as _};
+use serde::Serialize;
+use sqlx::{types::Json, Database, Executor, FromRow, Postgres};
 use uuid::Uuid;
 
-use crate::Cache;
+use crate::{
+    backend::{Backend, Encodes},
+    cursor::CursorSigner,
+    error::{CompileError, Diagnostic, LsorError},
+    Cache,
+};
 
-pub struct Driver {
+/// Collects PRQL and its bound arguments for the database `DB`.
+pub struct Driver<DB: Backend = Postgres> {
     prql: String,
-    arguments: PgArguments,
+    arguments: DB::Args<'static>,
+    binds: usize,
     cache: Option<Box<dyn Cache + Send + Sync + 'static>>,
+    cursor_signer: Option<CursorSigner>,
 }
 
 impl Driver {
     pub fn new() -> Self {
+        Self::empty()
+    }
+
+    pub fn with_cache(cache: Box<dyn Cache + Send + Sync + 'static>) -> Self {
+        Self::empty_with_cache(cache)
+    }
+}
+
+impl<DB: Backend> Driver<DB> {
+    /// Creates a driver for any backend. Postgres users can use `Driver::new`
+    /// instead.
+    pub fn empty() -> Self {
         Driver {
             prql: String::new(),
-            arguments: PgArguments::default(),
+            arguments: Default::default(),
+            binds: 0,
             cache: None,
+            cursor_signer: None,
         }
     }
 
-    pub fn with_cache(cache: Box<dyn Cache + Send + Sync +'static>) -> Self {
+    pub fn empty_with_cache(cache: Box<dyn Cache + Send + Sync + 'static>) -> Self {
         Driver {
             prql: String::new(),
-            arguments: PgArguments::default(),
+            arguments: Default::default(),
+            binds: 0,
             cache: Some(cache),
+            cursor_signer: None,
         }
     }
 
+    /// Signs the cursors handed out while paginating, and rejects cursors that
+    /// were not signed by the same signer.
+    pub fn with_cursor_signer(mut self, signer: CursorSigner) -> Self {
+        self.cursor_signer = Some(signer);
+        self
+    }
+
+    pub fn cursor_signer(&self) -> Option<&CursorSigner> {
+        self.cursor_signer.as_ref()
+    }
+
     pub fn prql(&self) -> &str {
         &self.prql
     }
 
-    pub fn sql(&self) -> String {
-        use prqlc::{sql::Dialect, Options, Target};
+    pub fn binds(&self) -> usize {
+        self.binds
+    }
+
+    /// Compiles the PRQL that has been pushed so far into SQL. If the PRQL
+    /// is invalid, the error contains the PRQL source and the diagnostics that
+    /// were reported by the compiler.
+    pub fn try_sql(&self) -> Result<String, LsorError> {
+        use prqlc::{Options, Target};
 
         let opts = &Options {
             format: false,
             signature_comment: false,
-            target: Target::Sql(Some(Dialect::Postgres)),
+            color: false,
+            target: Target::Sql(Some(DB::dialect())),
             ..Default::default()
         };
 
-        let cached_sql = self.fetch_from_cache(&self.prql);
+        // the same PRQL compiles into different SQL for different dialects
+        let key = format!("{}:{}", DB::dialect(), self.prql);
+        let cached_sql = self.fetch_from_cache(&key);
         if let Some(cached_sql) = cached_sql {
             tracing::debug!("returning cached sql:\n{}", &cached_sql);
-            return cached_sql.clone();
+            return Ok(cached_sql);
         }
 
         match prqlc::compile(&self.prql, opts) {
             Ok(sql) => {
+                let sql = DB::placeholders(sql);
                 tracing::debug!("compiling prql:\n{}\ninto sql:\n{}", &self.prql, &sql);
-                self.add_to_cache(self.prql.clone(), sql.clone());
-                sql
+                self.add_to_cache(key, sql.clone());
+                Ok(sql)
             }
-            Err(e) => {
-                tracing::error!("bad prql:\n{}", &self.prql);
-                tracing::error!("prql error:\n{}", e);
-                // FIXME: this is a hack; but it's better than panicking
-                //        we should find a way to make /sure/ that all code paths result in valid prql
-                //        otherwise we should return a prql from this function
-                "".into()
+            Err(e) => Err(CompileError {
+                prql: self.prql.clone(),
+                diagnostics: e.inner.into_iter().map(Diagnostic::from).collect(),
+                binds: self.binds,
             }
+            .into()),
         }
     }
 
+    /// Like `try_sql`, but logs compilation errors and returns an empty string
+    /// instead of failing.
+    pub fn sql(&self) -> String {
+        self.try_sql().unwrap_or_else(|e| {
+            tracing::error!("{}", e);
+            "".into()
+        })
+    }
+
     pub fn is_empty(&self) -> bool {
         self.prql.is_empty()
     }
@@ -76,64 +131,192 @@ impl Driver {
         write!(&mut self.prql, "{}", prql).expect("must write pqrl");
     }
 
+    /// Binds a value and pushes its `$n` placeholder. The placeholder is
+    /// rewritten into the native syntax of the backend before execution.
     pub fn push_bind<T>(&mut self, value: T)
     where
-        for<'q> T: Encode<'q, Postgres> + Send + Type<Postgres>,
+        DB: Encodes<T>,
     {
-        use sqlx::Arguments as _;
+        <DB as Encodes<T>>::add_argument(&mut self.arguments, value);
+        self.binds += 1;
+        self.push('$');
+        self.push(self.binds);
+    }
+
+    /// Binds the values that `push` binds, but does not push their
+    /// placeholders. Returns the numbers of the placeholders, so that SQL
+    /// statements can refer to the values in a different order.
+    pub fn bind_without_pushing(&mut self, push: impl FnOnce(&mut Self)) -> Range<usize> {
+        let len = self.prql.len();
+        let start = self.binds + 1;
+        push(self);
+        self.prql.truncate(len);
+        start..self.binds + 1
+    }
+
+    /// Compiles a query into SQL and pushes it. This is how SQL statements
+    /// that PRQL cannot express (such as updates and deletes) select the rows
+    /// that they write to. The query shares its bound arguments with the
+    /// statement.
+    pub fn push_subquery(&mut self, query: &impl PushPrql<DB>) -> Result<(), LsorError> {
+        let mut subquery = Driver {
+            prql: String::new(),
+            arguments: std::mem::take(&mut self.arguments),
+            binds: self.binds,
+            cache: self.cache.take(),
+            cursor_signer: None,
+        };
+        query.push_to_driver(&mut subquery);
+        let sql = subquery.try_sql();
+        self.arguments = subquery.arguments;
+        self.binds = subquery.binds;
+        self.cache = subquery.cache;
+        self.push(sql?);
+        Ok(())
+    }
 
-        self.arguments.add(value);
-        self.arguments
-            .format_placeholder(&mut self.prql)
-            .expect("must format placeholder");
+    /// Binds a value as JSON.
+    pub fn push_bind_json<T>(&mut self, value: &T)
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push_bind(Json(
+            serde_json::to_value(value).expect("must serialize json"),
+        ));
     }
 
     pub async fn execute_without_compilation<'c>(
         self,
-        executor: impl Executor<'c, Database = Postgres>,
-    ) -> sqlx::Result<<Postgres as Database>::QueryResult> {
-        use sqlx::QueryBuilder;
-
-        QueryBuilder::with_arguments(self.prql, self.arguments)
-            .build()
+        executor: impl Executor<'c, Database = DB>,
+    ) -> Result<<DB as Database>::QueryResult, LsorError> {
+        let sql = DB::placeholders(self.prql);
+        Ok(sqlx::query_with(&sql, DB::args(self.arguments))
             .execute(executor)
-            .await
+            .await?)
     }
 
-    pub async fn fetch_all(
+    pub async fn fetch_one_without_compilation<'c>(
         self,
-        executor: impl Executor<'_, Database = Postgres>,
-    ) -> sqlx::Result<Vec<<Postgres as Database>::Row>> {
-        use sqlx::QueryBuilder;
+        executor: impl Executor<'c, Database = DB>,
+    ) -> Result<<DB as Database>::Row, LsorError> {
+        let sql = DB::placeholders(self.prql);
+        Ok(sqlx::query_with(&sql, DB::args(self.arguments))
+            .fetch_one(executor)
+            .await?)
+    }
 
-        QueryBuilder::with_arguments(self.sql(), self.arguments)
-            .build()
+    pub async fn fetch_all(
+        self,
+        executor: impl Executor<'_, Database = DB>,
+    ) -> Result<Vec<<DB as Database>::Row>, LsorError> {
+        let sql = self.try_sql()?;
+        Ok(sqlx::query_with(&sql, DB::args(self.arguments))
             .fetch_all(executor)
-            .await
+            .await?)
     }
 
     pub async fn fetch_one(
         self,
-        executor: impl Executor<'_, Database = Postgres>,
-    ) -> sqlx::Result<<Postgres as Database>::Row> {
-        use sqlx::QueryBuilder;
-
-        QueryBuilder::with_arguments(self.sql(), self.arguments)
-            .build()
+        executor: impl Executor<'_, Database = DB>,
+    ) -> Result<<DB as Database>::Row, LsorError> {
+        let sql = self.try_sql()?;
+        Ok(sqlx::query_with(&sql, DB::args(self.arguments))
             .fetch_one(executor)
-            .await
+            .await?)
     }
 
     pub async fn fetch_optional(
         self,
-        executor: impl Executor<'_, Database = Postgres>,
-    ) -> sqlx::Result<Option<<Postgres as Database>::Row>> {
-        use sqlx::QueryBuilder;
-
-        QueryBuilder::with_arguments(self.sql(), self.arguments)
-            .build()
+        executor: impl Executor<'_, Database = DB>,
+    ) -> Result<Option<<DB as Database>::Row>, LsorError> {
+        let sql = self.try_sql()?;
+        Ok(sqlx::query_with(&sql, DB::args(self.arguments))
             .fetch_optional(executor)
-            .await
+            .await?)
+    }
+
+    pub async fn fetch_all_as<R>(
+        self,
+        executor: impl Executor<'_, Database = DB>,
+    ) -> Result<Vec<R>, LsorError>
+    where
+        for<'r> R: FromRow<'r, DB::Row>,
+    {
+        let rows = self.fetch_all(executor).await?;
+        Ok(rows.iter().map(R::from_row).collect::<sqlx::Result<_>>()?)
+    }
+
+    pub async fn fetch_one_as<R>(
+        self,
+        executor: impl Executor<'_, Database = DB>,
+    ) -> Result<R, LsorError>
+    where
+        for<'r> R: FromRow<'r, DB::Row>,
+    {
+        let row = self.fetch_one(executor).await?;
+        Ok(R::from_row(&row)?)
+    }
+
+    pub async fn fetch_optional_as<R>(
+        self,
+        executor: impl Executor<'_, Database = DB>,
+    ) -> Result<Option<R>, LsorError>
+    where
+        for<'r> R: FromRow<'r, DB::Row>,
+    {
+        let row = self.fetch_optional(executor).await?;
+        Ok(row.as_ref().map(R::from_row).transpose()?)
+    }
+
+    /// Fetches the first column of the first row, e.g. of a count.
+    pub async fn fetch_scalar<T>(
+        self,
+        executor: impl Executor<'_, Database = DB>,
+    ) -> Result<T, LsorError>
+    where
+        T: Send + Unpin,
+        (T,): for<'r> FromRow<'r, DB::Row>,
+    {
+        let sql = self.try_sql()?;
+        Ok(sqlx::query_scalar_with(&sql, DB::args(self.arguments))
+            .fetch_one(executor)
+            .await?)
+    }
+
+    /// Like `fetch_all_as`, but yields the rows one at a time as they are
+    /// received from the database, instead of collecting them up front.
+    pub fn fetch_stream_as<'e, R>(
+        self,
+        executor: impl 'e + Executor<'e, Database = DB>,
+    ) -> impl Stream<Item = Result<R, LsorError>> + 'e
+    where
+        DB: 'e,
+        for<'r> R: 'e + FromRow<'r, DB::Row>,
+    {
+        async_stream::try_stream! {
+            let sql = self.try_sql()?;
+            let mut rows = sqlx::query_with(&sql, DB::args(self.arguments)).fetch(executor);
+            while let Some(row) = rows.try_next().await? {
+                yield R::from_row(&row)?;
+            }
+        }
+    }
+
+    /// Estimates how many rows the query selects from the statistics of the
+    /// query planner, without running it. Returns `None` if the backend cannot
+    /// estimate rows.
+    pub async fn estimate_rows(
+        self,
+        executor: impl Executor<'_, Database = DB>,
+    ) -> Result<Option<i64>, LsorError> {
+        let sql = self.try_sql()?;
+        let Some(sql) = DB::explain(&sql) else {
+            return Ok(None);
+        };
+        let row = sqlx::query_with(&sql, DB::args(self.arguments))
+            .fetch_one(executor)
+            .await?;
+        Ok(Some(DB::estimated_rows(&row)?))
     }
 
     fn add_to_cache(&self, key: String, value: String) {
@@ -147,111 +330,114 @@ impl Driver {
     }
 }
 
-impl Default for Driver {
+impl<DB: Backend> Default for Driver<DB> {
     fn default() -> Self {
-        Self::new()
+        Self::empty()
     }
 }
 
-pub trait PushPrql {
-    fn push_to_driver(&self, driver: &mut Driver);
+pub trait PushPrql<DB: Backend = Postgres> {
+    fn push_to_driver(&self, driver: &mut Driver<DB>);
 }
 
-impl PushPrql for String {
-    fn push_to_driver(&self, driver: &mut Driver) {
-        driver.push_bind(self);
+impl<DB: Backend> PushPrql<DB> for String {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        driver.push_bind(self.clone());
     }
 }
 
-impl PushPrql for &str {
-    fn push_to_driver(&self, driver: &mut Driver) {
-        driver.push_bind(self);
+impl<DB: Backend> PushPrql<DB> for &str {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        driver.push_bind(self.to_string());
     }
 }
 
-impl PushPrql for i32 {
-    fn push_to_driver(&self, driver: &mut Driver) {
-        driver.push_bind(self);
+impl<DB: Backend> PushPrql<DB> for i32 {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        driver.push_bind(*self);
     }
 }
 
-impl PushPrql for i64 {
-    fn push_to_driver(&self, driver: &mut Driver) {
-        driver.push_bind(self);
+impl<DB: Backend> PushPrql<DB> for i64 {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        driver.push_bind(*self);
     }
 }
 
-impl PushPrql for u32 {
-    fn push_to_driver(&self, driver: &mut Driver) {
+impl<DB: Backend> PushPrql<DB> for u32 {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         driver.push_bind(*self as i32);
     }
 }
 
-impl PushPrql for u64 {
-    fn push_to_driver(&self, driver: &mut Driver) {
+impl<DB: Backend> PushPrql<DB> for u64 {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         driver.push_bind(*self as i64);
     }
 }
 
-impl PushPrql for f32 {
-    fn push_to_driver(&self, driver: &mut Driver) {
+impl<DB: Backend> PushPrql<DB> for f32 {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         driver.push_bind(*self);
     }
 }
 
-impl PushPrql for f64 {
-    fn push_to_driver(&self, driver: &mut Driver) {
+impl<DB: Backend> PushPrql<DB> for f64 {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         driver.push_bind(*self);
     }
 }
 
-impl PushPrql for bool {
-    fn push_to_driver(&self, driver: &mut Driver) {
-        driver.push_bind(self);
+impl<DB: Backend> PushPrql<DB> for bool {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        driver.push_bind(*self);
     }
 }
 
-impl PushPrql for Uuid {
-    fn push_to_driver(&self, driver: &mut Driver) {
-        driver.push_bind(self);
+impl<DB: Backend> PushPrql<DB> for Uuid {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        driver.push_bind(*self);
     }
 }
 
-impl PushPrql for DateTime<Utc> {
-    fn push_to_driver(&self, driver: &mut Driver) {
-        driver.push_bind(self);
+impl<DB: Backend> PushPrql<DB> for DateTime<Utc> {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        driver.push_bind(*self);
     }
 }
 
-impl<T> PushPrql for Option<T>
+impl<DB, T> PushPrql<DB> for Option<T>
 where
-    for<'q> T: 'q + Encode<'q, Postgres> + Sync + Type<Postgres>,
+    DB: Backend + Encodes<Option<T>>,
+    T: Clone,
 {
-    fn push_to_driver(&self, driver: &mut Driver) {
-        driver.push_bind(self);
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        driver.push_bind(self.clone());
     }
 }
 
-impl<T> PushPrql for Vec<T>
+impl<DB, T> PushPrql<DB> for Vec<T>
 where
-    for<'q> T: 'q + Encode<'q, Postgres> + Sync + Type<Postgres> + PgHasArrayType,
+    DB: Backend + Encodes<Vec<T>>,
+    T: Clone,
 {
-    fn push_to_driver(&self, driver: &mut Driver) {
-        driver.push_bind(self);
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        driver.push_bind(self.clone());
     }
 }
 
-impl<T> PushPrql for &T
+impl<DB, T> PushPrql<DB> for &T
 where
-    T: PushPrql,
+    DB: Backend,
+    T: PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut Driver) {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         (*self).push_to_driver(driver);
     }
 }
 
-impl PushPrql for &dyn PushPrql {
-    fn push_to_driver(&self, driver: &mut Driver) {
+impl<DB: Backend> PushPrql<DB> for &dyn PushPrql<DB> {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         (*self).push_to_driver(driver)
     }
 }
@@ -264,10 +450,52 @@ pub struct SQL {
     pub sql: &'static str,
 }
 
-impl PushPrql for SQL {
-    fn push_to_driver(&self, driver: &mut crate::driver::Driver) {
+impl<DB: Backend> PushPrql<DB> for SQL {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         driver.push("s\"");
         driver.push(self.sql);
         driver.push('\"');
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{column::col, cond::gt, from::from, table::table};
+
+    use super::*;
+
+    #[test]
+    fn test_try_sql() {
+        let mut driver = Driver::new();
+        {
+            from(table("users"))
+                .filter(gt(col("age"), 18))
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.try_sql().unwrap(),
+            "SELECT * FROM users WHERE age > $1"
+        );
+    }
+
+    #[test]
+    fn test_try_sql_with_bad_prql() {
+        let mut driver = Driver::new();
+        driver.push("from users\nfilter ");
+        driver.push_bind(18);
+        driver.push(" >");
+
+        let err = driver.try_sql().unwrap_err();
+        assert!(err.is_compile());
+        match err {
+            LsorError::Compile(e) => {
+                assert_eq!(e.prql, "from users\nfilter $1 >");
+                assert_eq!(e.binds, 1);
+                assert!(!e.diagnostics.is_empty());
+                assert!(e.diagnostics.iter().any(|d| d.span.is_some()));
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(driver.sql(), "");
+    }
+}
@@ -1,4 +1,4 @@
-use crate::driver::PushPrql;
+use crate::{backend::Backend, driver::PushPrql};
 
 pub fn if_then_else<Then, Else>(
     cond: bool,
@@ -17,12 +17,12 @@ pub enum Either<Left, Right> {
     Right(Right),
 }
 
-impl<Left, Right> PushPrql for Either<Left, Right>
+impl<DB: Backend, Left, Right> PushPrql<DB> for Either<Left, Right>
 where
-    Left: PushPrql,
-    Right: PushPrql,
+    Left: PushPrql<DB>,
+    Right: PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut crate::driver::Driver) {
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
         match self {
             Either::Left(left) => left.push_to_driver(driver),
             Either::Right(right) => right.push_to_driver(driver),
@@ -0,0 +1,217 @@
+use std::{fmt, ops::Range};
+
+/// The error type returned by everything in lsor that talks to the database.
+///
+/// Keeping compilation failures separate from database failures means that
+/// callers can tell a bug in the emitted PRQL apart from a query that was
+/// rejected (or failed) at runtime.
+#[derive(Debug)]
+pub enum LsorError {
+    /// The PRQL emitted by the driver could not be compiled into SQL.
+    Compile(CompileError),
+    /// The SQL was sent to the database and the database returned an error.
+    Database(sqlx::Error),
+    /// A cursor passed in by the caller could not be decoded.
+    Cursor(CursorError),
+    /// A write statement could not be built.
+    Statement(StatementError),
+}
+
+impl LsorError {
+    pub fn is_compile(&self) -> bool {
+        matches!(self, Self::Compile(_))
+    }
+
+    pub fn is_database(&self) -> bool {
+        matches!(self, Self::Database(_))
+    }
+
+    pub fn is_cursor(&self) -> bool {
+        matches!(self, Self::Cursor(_))
+    }
+
+    pub fn is_statement(&self) -> bool {
+        matches!(self, Self::Statement(_))
+    }
+}
+
+impl fmt::Display for LsorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Compile(e) => e.fmt(f),
+            Self::Database(e) => write!(f, "database error: {}", e),
+            Self::Cursor(e) => e.fmt(f),
+            Self::Statement(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for LsorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Compile(e) => Some(e),
+            Self::Database(e) => Some(e),
+            Self::Cursor(e) => Some(e),
+            Self::Statement(e) => Some(e),
+        }
+    }
+}
+
+impl From<CompileError> for LsorError {
+    fn from(e: CompileError) -> Self {
+        Self::Compile(e)
+    }
+}
+
+impl From<sqlx::Error> for LsorError {
+    fn from(e: sqlx::Error) -> Self {
+        Self::Database(e)
+    }
+}
+
+impl From<CursorError> for LsorError {
+    fn from(e: CursorError) -> Self {
+        Self::Cursor(e)
+    }
+}
+
+impl From<StatementError> for LsorError {
+    fn from(e: StatementError) -> Self {
+        Self::Statement(e)
+    }
+}
+
+/// Why a cursor was rejected. Cursors come from clients, so these errors are
+/// safe to show to them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CursorError {
+    /// The cursor is not something that lsor could have handed out.
+    Malformed { cursor: String },
+    /// The cursor was encoded with a version of the format that is not
+    /// supported (anymore).
+    UnsupportedVersion { cursor: String, version: u8 },
+    /// The signature of the cursor does not match its contents.
+    Forged { cursor: String },
+    /// The cursor does not hold exactly one value for every sort key.
+    Mismatched {
+        cursor: String,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for CursorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed { cursor } => write!(f, "malformed cursor '{}'", cursor),
+            Self::UnsupportedVersion { cursor, version } => {
+                write!(f, "unsupported cursor version {} in '{}'", version, cursor)
+            }
+            Self::Forged { cursor } => write!(f, "invalid signature for cursor '{}'", cursor),
+            Self::Mismatched {
+                cursor,
+                expected,
+                found,
+            } => write!(
+                f,
+                "cursor '{}' has {} values but the sorting has {} keys",
+                cursor, found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CursorError {}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StatementError {
+    /// The driver already holds a query or statement.
+    NotEmpty,
+    /// The rows to write to cannot be found without a primary key.
+    MissingPrimaryKey { table: &'static str },
+    /// There are no columns to update.
+    NothingToUpdate { table: &'static str },
+}
+
+impl fmt::Display for StatementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotEmpty => write!(f, "statements must be pushed into an empty driver"),
+            Self::MissingPrimaryKey { table } => {
+                write!(
+                    f,
+                    "cannot write to rows of '{}' without a primary key",
+                    table
+                )
+            }
+            Self::NothingToUpdate { table } => {
+                write!(f, "no columns to update in '{}'", table)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StatementError {}
+
+/// Everything we know about a PRQL query that failed to compile.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CompileError {
+    /// The PRQL source that was handed to the compiler.
+    pub prql: String,
+    /// The diagnostics reported by the compiler.
+    pub diagnostics: Vec<Diagnostic>,
+    /// The number of arguments that were bound to the driver.
+    pub binds: usize,
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bad prql ({} binds):\n{}", self.binds, self.prql)?;
+        for diagnostic in &self.diagnostics {
+            write!(f, "\n{}", diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+/// A single diagnostic reported by the PRQL compiler.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    /// Machine-readable identifier of the error (if the compiler has one).
+    pub code: Option<String>,
+    /// Plain text description of the error.
+    pub reason: String,
+    /// Suggestions for fixing the error.
+    pub hints: Vec<String>,
+    /// Byte offsets of the error within the PRQL source.
+    pub span: Option<Range<usize>>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(code) = &self.code {
+            write!(f, "[{}] ", code)?;
+        }
+        write!(f, "error: {}", self.reason)?;
+        if let Some(span) = &self.span {
+            write!(f, " (at {}..{})", span.start, span.end)?;
+        }
+        for hint in &self.hints {
+            write!(f, "\n  hint: {}", hint)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<prqlc::ErrorMessage> for Diagnostic {
+    fn from(e: prqlc::ErrorMessage) -> Self {
+        Self {
+            code: e.code,
+            reason: e.reason,
+            hints: e.hints,
+            span: e.span.map(Range::from),
+        }
+    }
+}
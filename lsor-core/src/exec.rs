@@ -1,115 +1,496 @@
-use async_graphql::{
-    connection::{Connection, Edge, PageInfo},
-    OutputType,
-};
-use sqlx::{postgres::PgRow, Executor, FromRow, Postgres};
+#[cfg(feature = "graphql")]
+use async_graphql::{connection::Connection, OutputType};
+use std::collections::VecDeque;
+
+use futures_util::{stream, Stream};
+use sqlx::{postgres::PgRow, Acquire, Executor, FromRow, Postgres};
 
+#[cfg(feature = "graphql")]
+use crate::page::TotalCount;
 use crate::{
-    cursor::Cursor,
+    aggregate::Aggregate,
+    backend::{try_get, Backend},
+    column::{col, ColumnName},
+    cursor::{CompositeCursor, Cursor},
     driver::{Driver, PushPrql},
+    error::LsorError,
+    expr::count,
     from::from,
-    page::{select_page_info, select_page_items, Pagination, TotalCount},
-    row::{upsert, Row},
+    page::{
+        keyset_after, select_page, with_cursor_columns, CountStrategy, CursorColumn,
+        KeysetFiltered, OffsetPage, OffsetPagination, Page, PageInfo, PageItem, Pagination,
+    },
+    row::{upsert, upsert_many, Row, Upserted},
+    sort::{multi_sort, not_null, then_by, MultiSort, NotNull, Sort, Sorted, Sorting, ThenBy},
+    sql::{delete_where, insert, update, update_where, PushSql},
     table::Table,
-    Sorting,
+    take::TakenRange,
+    var::Var,
 };
 
-pub async fn save_one<'c, E, R>(mut driver: Driver, executor: E, row: R) -> sqlx::Result<()>
+pub async fn save_one<'c, DB, E, R>(
+    mut driver: Driver<DB>,
+    executor: E,
+    row: R,
+) -> Result<(), LsorError>
 where
-    E: Executor<'c, Database = Postgres>,
-    R: Row + Table,
+    DB: Backend,
+    E: Executor<'c, Database = DB>,
+    R: Row<DB> + Table,
 {
     upsert(row).push_to_driver(&mut driver);
     driver.execute_without_compilation(executor).await?;
     Ok(())
 }
 
-pub async fn load_one<'c, E, F, R>(mut driver: Driver, executor: E, filter: F) -> sqlx::Result<Option<R>>
+/// Upserts a row and returns it as it was written, so that database defaults
+/// and values computed by triggers do not need to be queried separately.
+pub async fn save_one_returning<'c, E, R>(
+    mut driver: Driver<Postgres>,
+    executor: E,
+    row: R,
+) -> Result<Upserted<R>, LsorError>
 where
     E: Executor<'c, Database = Postgres>,
-    F: PushPrql,
-    for<'r> R: FromRow<'r, PgRow> + Table,
+    for<'r> R: FromRow<'r, PgRow> + Row<Postgres> + Table,
+{
+    upsert(row).returning().push_to_driver(&mut driver);
+    let row = driver.fetch_one_without_compilation(executor).await?;
+    Ok(Upserted::from_row(&row)?)
+}
+
+/// Upserts all rows in one transaction, using as few statements as the
+/// backend's limit on bound arguments allows. The driver is used for the first
+/// statement.
+pub async fn save_many<'a, DB, A, R>(
+    driver: Driver<DB>,
+    conn: A,
+    rows: Vec<R>,
+) -> Result<(), LsorError>
+where
+    DB: Backend,
+    A: Acquire<'a, Database = DB>,
+    for<'e> &'e mut DB::Connection: Executor<'e, Database = DB>,
+    R: Row<DB> + Table,
+{
+    let mut tx = conn.begin().await?;
+    let mut driver = Some(driver);
+    for chunk in upsert_many(rows).chunks::<DB>() {
+        // the statements after the first are executed from an empty driver
+        let mut driver = driver.take().unwrap_or_else(Driver::empty);
+        chunk.push_to_driver(&mut driver);
+        driver.execute_without_compilation(&mut *tx).await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Inserts a row, and fails if it conflicts with an existing row. Returns the
+/// number of inserted rows.
+pub async fn insert_one<'c, DB, E, R>(
+    mut driver: Driver<DB>,
+    executor: E,
+    row: R,
+) -> Result<u64, LsorError>
+where
+    DB: Backend,
+    E: Executor<'c, Database = DB>,
+    R: Row<DB> + Table,
+{
+    insert(row).push_sql(&mut driver)?;
+    let result = driver.execute_without_compilation(executor).await?;
+    Ok(DB::rows_affected(&result))
+}
+
+/// Updates the row with the same primary key. Returns the number of updated
+/// rows, which is zero if there is no such row.
+pub async fn update_one<'c, DB, E, R>(
+    mut driver: Driver<DB>,
+    executor: E,
+    row: R,
+) -> Result<u64, LsorError>
+where
+    DB: Backend,
+    E: Executor<'c, Database = DB>,
+    R: Row<DB> + Table,
+{
+    update(row).push_sql(&mut driver)?;
+    let result = driver.execute_without_compilation(executor).await?;
+    Ok(DB::rows_affected(&result))
+}
+
+/// Sets the columns of the patch on all rows of `R` that match the filter.
+/// Returns the number of updated rows.
+pub async fn update_many<'c, DB, E, F, P, R>(
+    mut driver: Driver<DB>,
+    executor: E,
+    filter: F,
+    patch: P,
+) -> Result<u64, LsorError>
+where
+    DB: Backend,
+    E: Executor<'c, Database = DB>,
+    F: PushPrql<DB>,
+    P: Row<DB>,
+    R: Row<DB> + Table,
+{
+    update_where::<R, F, P>(filter, patch).push_sql(&mut driver)?;
+    let result = driver.execute_without_compilation(executor).await?;
+    Ok(DB::rows_affected(&result))
+}
+
+/// Deletes all rows of `R` that match the filter. Returns the number of
+/// deleted rows.
+pub async fn delete_many<'c, DB, E, F, R>(
+    mut driver: Driver<DB>,
+    executor: E,
+    filter: F,
+) -> Result<u64, LsorError>
+where
+    DB: Backend,
+    E: Executor<'c, Database = DB>,
+    F: PushPrql<DB>,
+    R: Row<DB> + Table,
+{
+    delete_where::<R, F>(filter).push_sql(&mut driver)?;
+    let result = driver.execute_without_compilation(executor).await?;
+    Ok(DB::rows_affected(&result))
+}
+
+pub async fn load_one<'c, DB, E, F, R>(
+    mut driver: Driver<DB>,
+    executor: E,
+    filter: F,
+) -> Result<Option<R>, LsorError>
+where
+    DB: Backend,
+    E: Executor<'c, Database = DB>,
+    F: PushPrql<DB>,
+    for<'r> R: FromRow<'r, DB::Row> + Table,
 {
     from(R::table_name())
         .filter(filter)
         .take(1)
         .push_to_driver(&mut driver);
 
-    driver
-        .fetch_optional(executor)
-        .await
-        .and_then(|row| row.as_ref().map(R::from_row).transpose())
+    driver.fetch_optional_as(executor).await
 }
 
-pub async fn load_page<'c, E, F, S, R>(
-    mut driver: Driver,
+#[cfg(feature = "graphql")]
+pub async fn load_page<'c, DB, E, F, S, R>(
+    driver: Driver<DB>,
     executor: E,
     filter: F,
     sort: S,
     pagination: Pagination,
-) -> sqlx::Result<Connection<String, R, TotalCount>>
+) -> Result<Connection<String, R, TotalCount>, LsorError>
 where
-    E: Copy + Executor<'c, Database = Postgres>,
-    F: PushPrql,
-    S: PushPrql + Sorting,
-    for<'r> R: FromRow<'r, PgRow> + OutputType + Table,
+    DB: Backend,
+    E: Copy + Executor<'c, Database = DB>,
+    F: PushPrql<DB>,
+    S: PushPrql<DB> + Sorting<DB>,
+    for<'r> R: FromRow<'r, DB::Row> + OutputType + Row<DB> + Table,
 {
-    use sqlx::Row;
+    let page = load_page_raw(driver, executor, filter, sort, pagination).await?;
+    Ok(page.into())
+}
+
+pub async fn load_page_raw<'c, DB, E, F, S, R>(
+    mut driver: Driver<DB>,
+    executor: E,
+    filter: F,
+    sort: S,
+    pagination: Pagination,
+) -> Result<Page<R>, LsorError>
+where
+    DB: Backend,
+    E: Copy + Executor<'c, Database = DB>,
+    F: PushPrql<DB>,
+    S: PushPrql<DB> + Sorting<DB>,
+    for<'r> R: FromRow<'r, DB::Row> + Row<DB> + Table,
+{
+    // the cursor of the pagination is for the leading key of the caller's
+    // sorting, which is the primary key when the sorting is empty
+    let cursor = (sort.num_keys() > 0).then_some(pagination.cursor);
+    let sort = then_by_pk::<DB, _, R>(&sort);
+
+    let count = pagination.count;
+    let num_keys = sort.num_keys();
+    let signer = driver.cursor_signer().cloned();
+    let pagination = pagination.decode(signer.as_ref(), num_keys)?;
+    let (first, last) = (pagination.first, pagination.last);
+    let (probe_prev, probe_next) = (pagination.after.is_some(), pagination.before.is_some());
 
-    let cursor = pagination.cursor;
     let subquery = from(R::table_name()).filter(filter);
-    let subquery = subquery.sort(&sort);
-    let subquery_with_cursor = subquery.derive("cursor", &sort);
 
-    select_page_items(&subquery_with_cursor, pagination).push_to_driver(&mut driver);
+    // the estimate is taken up front, so that backends that cannot estimate
+    // rows can fall back to counting them alongside the page
+    let estimate = match count {
+        CountStrategy::Estimated => {
+            let mut estimate = Driver::<DB>::empty();
+            subquery.push_to_driver(&mut estimate);
+            estimate.estimate_rows(executor).await?
+        }
+        CountStrategy::Exact | CountStrategy::None => None,
+    };
+    let exact = match count {
+        CountStrategy::Exact => true,
+        CountStrategy::Estimated => estimate.is_none(),
+        CountStrategy::None => false,
+    };
+    select_page(&subquery, &sort, pagination, exact).push_to_driver(&mut driver);
 
     let rows = driver.fetch_all(executor).await?;
-    let edges = rows
+    // the page info is joined onto every row, and is selected on its own when
+    // the page is empty
+    let info = rows.first();
+    let get_count = |name: &'static str| {
+        info.map(|row| try_get::<DB, i64>(row, name))
+            .unwrap_or(Ok(0))
+    };
+    let mut items = Vec::with_capacity(rows.len());
+    for row in &rows {
+        if !DB::is_null(row, "page_item")? {
+            items.push(row);
+        }
+    }
+
+    // trim off the extra rows that tell whether there are more rows
+    let mut has_next_page = probe_next && get_count("next_count")? > 0;
+    let mut has_previous_page = probe_prev && get_count("prev_count")? > 0;
+    if let Some(n) = first {
+        if items.len() > n {
+            items.truncate(n);
+            has_next_page = true;
+        }
+    }
+    if let Some(n) = last {
+        if items.len() > n {
+            items.drain(..items.len() - n);
+            has_previous_page = true;
+        }
+    }
+
+    let items = items
         .into_iter()
         .map(|row| {
-            Ok(Edge::new(
-                Cursor::infer(row.try_get_raw("cursor")?)?,
-                R::from_row(&row)?,
-            ))
+            let cursor = row_cursor::<DB>(row, cursor, num_keys)?;
+            let cursor = match &signer {
+                Some(signer) => signer.sign(&cursor),
+                None => cursor.encode(),
+            };
+            Ok(PageItem {
+                cursor,
+                item: R::from_row(row)?,
+            })
         })
         .collect::<sqlx::Result<Vec<_>>>()?;
 
-    let start = edges
-        .first()
-        .map(|edge| edge.cursor.clone())
-        .unwrap_or(Cursor::encode(&cursor.min()));
-    let end = edges
-        .last()
-        .map(|edge| edge.cursor.clone())
-        .unwrap_or(Cursor::encode(&cursor.max()));
-
-    let mut driver = Driver::new();
-    select_page_info(subquery, cursor, start.clone(), end.clone()).push_to_driver(&mut driver);
-    let row = driver.fetch_optional(executor).await?;
-    let page_info = PageInfo {
-        has_next_page: row
-            .as_ref()
-            .map(|x| x.try_get("has_next_page"))
-            .unwrap_or(Ok(false))?,
-        has_previous_page: row
-            .as_ref()
-            .map(|x| x.try_get("has_prev_page"))
-            .unwrap_or(Ok(false))?,
-        start_cursor: Some(start),
-        end_cursor: Some(end),
+    Ok(Page {
+        page_info: PageInfo {
+            has_previous_page,
+            has_next_page,
+            start_cursor: items.first().map(|item| item.cursor.clone()),
+            end_cursor: items.last().map(|item| item.cursor.clone()),
+        },
+        items,
+        total_count: match estimate {
+            Some(estimate) => estimate,
+            None if exact => get_count("total_count")?,
+            None => 0,
+        },
+        total_count_is_estimate: estimate.is_some(),
+    })
+}
+
+pub async fn load_offset_page<'c, DB, E, F, S, R>(
+    mut driver: Driver<DB>,
+    executor: E,
+    filter: F,
+    sort: S,
+    pagination: OffsetPagination,
+) -> Result<OffsetPage<R>, LsorError>
+where
+    DB: Backend,
+    E: Copy + Executor<'c, Database = DB>,
+    F: PushPrql<DB>,
+    S: PushPrql<DB> + Sorting<DB>,
+    for<'r> R: FromRow<'r, DB::Row> + Row<DB> + Table,
+{
+    let pagination = OffsetPagination {
+        page: pagination.page.max(1),
+        per_page: pagination.per_page.max(1),
     };
-    let total_count = TotalCount {
-        total_count: row
-            .as_ref()
-            .map(|x| x.try_get("total_count"))
-            .unwrap_or(Ok(0))?,
+    let sort = then_by_pk::<DB, _, R>(&sort);
+
+    let subquery = from(R::table_name()).filter(filter);
+    TakenRange {
+        query: Sorted {
+            query: &subquery,
+            sort: &sort,
+        }
+        .derive("total_count", count()),
+        range: pagination.range(),
+    }
+    .push_to_driver(&mut driver);
+
+    let rows = driver.fetch_all(executor).await?;
+    let total_count = match rows.first() {
+        Some(row) => try_get::<DB, i64>(row, "total_count")?,
+        // pages past the end have no rows to read the count from
+        None if pagination.page > 1 => {
+            let mut driver = Driver::<DB>::empty();
+            Aggregate {
+                query: &subquery,
+                aggregations: vec![(col("total_count"), count())],
+            }
+            .push_to_driver(&mut driver);
+            let row = driver.fetch_one(executor).await?;
+            try_get::<DB, i64>(&row, "total_count")?
+        }
+        None => 0,
     };
+    let items = rows
+        .iter()
+        .map(R::from_row)
+        .collect::<sqlx::Result<Vec<_>>>()?;
 
-    let mut conn = Connection::with_additional_fields(
-        page_info.has_previous_page,
-        page_info.has_next_page,
+    Ok(OffsetPage {
+        items,
+        page: pagination.page,
+        per_page: pagination.per_page,
+        total_pages: pagination.total_pages(total_count),
         total_count,
+    })
+}
+
+/// Streams every row that matches the filter in the order of the sorting,
+/// loading `batch_size` rows at a time.
+///
+/// Every batch continues strictly after the last row of the previous batch,
+/// with ties broken by the primary key, so rows that are inserted or deleted
+/// while scanning never cause other rows to be skipped or repeated.
+pub fn scan<'c, DB, E, F, S, R>(
+    executor: E,
+    filter: F,
+    sort: S,
+    batch_size: usize,
+) -> impl Stream<Item = Result<R, LsorError>> + 'c
+where
+    DB: Backend,
+    E: 'c + Copy + Executor<'c, Database = DB>,
+    F: 'c + PushPrql<DB>,
+    S: 'c + PushPrql<DB> + Sorting<DB>,
+    for<'r> R: 'c + FromRow<'r, DB::Row> + Row<DB> + Table,
+{
+    let state = ScanState {
+        filter,
+        sort,
+        after: None,
+        batch: VecDeque::new(),
+        done: false,
+    };
+    stream::try_unfold(state, move |mut state| async move {
+        if state.batch.is_empty() && !state.done {
+            state
+                .load_batch::<E, DB>(executor, batch_size.max(1))
+                .await?;
+        }
+        Ok(state.batch.pop_front().map(|row| (row, state)))
+    })
+}
+
+struct ScanState<F, S, R> {
+    filter: F,
+    sort: S,
+    /// The cursor values of the last row that was loaded.
+    after: Option<Vec<Var>>,
+    batch: VecDeque<R>,
+    done: bool,
+}
+
+impl<F, S, R> ScanState<F, S, R> {
+    async fn load_batch<'c, E, DB>(
+        &mut self,
+        executor: E,
+        batch_size: usize,
+    ) -> Result<(), LsorError>
+    where
+        DB: Backend,
+        E: Executor<'c, Database = DB>,
+        F: PushPrql<DB>,
+        S: PushPrql<DB> + Sorting<DB>,
+        for<'r> R: FromRow<'r, DB::Row> + Row<DB> + Table,
+    {
+        let sort = then_by_pk::<DB, _, R>(&self.sort);
+        let num_keys = sort.num_keys();
+
+        let mut driver = Driver::<DB>::empty();
+        KeysetFiltered {
+            query: with_cursor_columns(
+                Sorted {
+                    query: from(R::table_name()).filter(&self.filter),
+                    sort: &sort,
+                },
+                &sort,
+            ),
+            after: self.after.take().map(|after| keyset_after(&sort, after)),
+            before: None,
+        }
+        .take(batch_size)
+        .push_to_driver(&mut driver);
+
+        let rows = driver.fetch_all(executor).await?;
+        self.done = rows.len() < batch_size;
+        if let Some(last) = rows.last() {
+            self.after = Some(
+                (0..num_keys)
+                    .map(|i| Cursor::infer_column::<DB>(last, &CursorColumn(i).name()))
+                    .collect::<sqlx::Result<_>>()?,
+            );
+        }
+        self.batch = rows.iter().map(R::from_row).collect::<sqlx::Result<_>>()?;
+        Ok(())
+    }
+}
+
+/// Sorts by the primary key after the given sorting, in the same order as the
+/// sorting. Pages over non-unique sortings can otherwise skip or repeat rows.
+fn then_by_pk<DB, S, R>(sort: S) -> ThenBy<S, NotNull<MultiSort<Sort<ColumnName>>>>
+where
+    DB: Backend,
+    S: Sorting<DB>,
+    R: Row<DB>,
+{
+    let order = sort.order();
+    let pks = multi_sort(
+        R::column_names()
+            .filter(|column| column.pk)
+            .map(|column| Sort {
+                order,
+                by: column.name,
+            })
+            .collect::<Vec<_>>(),
     );
-    conn.edges = edges;
-    Ok(conn)
+    then_by(sort, not_null(pks))
+}
+
+/// Decodes the `CursorColumn`s of a row into a composite cursor. The leading
+/// key is decoded as the given cursor (if any), and the types of the
+/// remaining keys (e.g. the primary key) are inferred.
+fn row_cursor<DB: Backend>(
+    row: &DB::Row,
+    cursor: Option<Cursor>,
+    num_keys: usize,
+) -> sqlx::Result<CompositeCursor> {
+    let values = (0..num_keys)
+        .map(|i| {
+            let column = CursorColumn(i).name();
+            match cursor {
+                Some(cursor) if i == 0 => cursor.decode_column::<DB>(row, &column),
+                _ => Cursor::infer_column::<DB>(row, &column),
+            }
+        })
+        .collect::<sqlx::Result<Vec<_>>>()?;
+    Ok(CompositeCursor::new(values))
 }
@@ -1,9 +1,15 @@
-use crate::driver::PushPrql;
+use sqlx::Postgres;
+
+use crate::{backend::Backend, cond::impl_ops, driver::PushPrql};
 
 pub fn add<LHS, RHS>(lhs: LHS, rhs: RHS) -> Add<LHS, RHS> {
     Add { lhs, rhs }
 }
 
+pub fn array_agg<Col>(col: Col) -> ArrayAgg<Col> {
+    ArrayAgg { col }
+}
+
 pub fn avg<Expr>(expr: Expr) -> Avg<Expr> {
     Avg { expr }
 }
@@ -16,10 +22,84 @@ pub fn case<const N: usize, Cond, Then>(
     }
 }
 
+pub fn coalesce<LHS, RHS>(lhs: LHS, rhs: RHS) -> Coalesce<LHS, RHS> {
+    Coalesce { lhs, rhs }
+}
+
+pub fn concat<Col>(cols: impl IntoIterator<Item = Col>) -> Concat<Col> {
+    Concat {
+        cols: cols.into_iter().collect(),
+    }
+}
+
 pub fn count() -> Count {
     Count {}
 }
 
+pub fn count_distinct<Expr>(expr: Expr) -> CountDistinct<Expr> {
+    CountDistinct { expr }
+}
+
+pub fn date_trunc<Col>(unit: DateUnit, col: Col) -> DateTrunc<Col> {
+    DateTrunc { unit, col }
+}
+
+pub fn div<LHS, RHS>(lhs: LHS, rhs: RHS) -> Div<LHS, RHS> {
+    Div { lhs, rhs }
+}
+
+pub fn ends_with<Expr, Suffix>(expr: Expr, suffix: Suffix) -> EndsWith<Expr, Suffix> {
+    EndsWith { expr, suffix }
+}
+
+pub fn extract<Col>(unit: DateUnit, col: Col) -> Extract<Col> {
+    Extract { unit, col }
+}
+
+pub fn interval(n: i64, unit: DateUnit) -> Interval {
+    Interval { n, unit }
+}
+
+pub fn length<Expr>(expr: Expr) -> Length<Expr> {
+    Length { expr }
+}
+
+pub fn lower<Expr>(expr: Expr) -> Lower<Expr> {
+    Lower { expr }
+}
+
+pub fn max<Expr>(expr: Expr) -> Max<Expr> {
+    Max { expr }
+}
+
+pub fn min<Expr>(expr: Expr) -> Min<Expr> {
+    Min { expr }
+}
+
+pub fn modulo<LHS, RHS>(lhs: LHS, rhs: RHS) -> Modulo<LHS, RHS> {
+    Modulo { lhs, rhs }
+}
+
+pub fn mul<LHS, RHS>(lhs: LHS, rhs: RHS) -> Mul<LHS, RHS> {
+    Mul { lhs, rhs }
+}
+
+pub fn neg<Expr>(expr: Expr) -> Neg<Expr> {
+    Neg { expr }
+}
+
+pub fn now() -> Now {
+    Now {}
+}
+
+pub fn starts_with<Expr, Prefix>(expr: Expr, prefix: Prefix) -> StartsWith<Expr, Prefix> {
+    StartsWith { expr, prefix }
+}
+
+pub fn stddev<Expr>(expr: Expr) -> Stddev<Expr> {
+    Stddev { expr }
+}
+
 pub fn sub<LHS, RHS>(lhs: LHS, rhs: RHS) -> Sub<LHS, RHS> {
     Sub { lhs, rhs }
 }
@@ -28,6 +108,10 @@ pub fn sum<Expr>(expr: Expr) -> Sum<Expr> {
     Sum { expr }
 }
 
+pub fn upper<Expr>(expr: Expr) -> Upper<Expr> {
+    Upper { expr }
+}
+
 pub fn when<Cond>(cond: Cond) -> When<Cond> {
     When { cond }
 }
@@ -37,27 +121,45 @@ pub struct Add<LHS, RHS> {
     pub rhs: RHS,
 }
 
-impl<LHS, RHS> PushPrql for Add<LHS, RHS>
+impl<DB: Backend, LHS, RHS> PushPrql<DB> for Add<LHS, RHS>
 where
-    LHS: PushPrql,
-    RHS: PushPrql,
+    LHS: PushPrql<DB>,
+    RHS: PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut crate::driver::Driver) {
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
         self.lhs.push_to_driver(driver);
         driver.push(" + ");
         self.rhs.push_to_driver(driver);
     }
 }
 
+/// Collects the values of a column into an array. PRQL has no such function,
+/// so it is pushed as an s-string, which can only interpolate columns. Only
+/// Postgres has arrays.
+pub struct ArrayAgg<Col> {
+    pub col: Col,
+}
+
+impl<Col> PushPrql<Postgres> for ArrayAgg<Col>
+where
+    Col: PushPrql<Postgres>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<Postgres>) {
+        driver.push("s\"array_agg({");
+        self.col.push_to_driver(driver);
+        driver.push("})\"");
+    }
+}
+
 pub struct Avg<Expr> {
     pub expr: Expr,
 }
 
-impl<Expr> PushPrql for Avg<Expr>
+impl<DB: Backend, Expr> PushPrql<DB> for Avg<Expr>
 where
-    Expr: PushPrql,
+    Expr: PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut crate::driver::Driver) {
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
         driver.push("average ");
         self.expr.push_to_driver(driver);
     }
@@ -79,12 +181,12 @@ impl<const N: usize, Cond, Then> Case<N, Cond, Then> {
     }
 }
 
-impl<const N: usize, Cond, Then> PushPrql for Case<N, Cond, Then>
+impl<DB: Backend, const N: usize, Cond, Then> PushPrql<DB> for Case<N, Cond, Then>
 where
-    Cond: PushPrql,
-    Then: PushPrql,
+    Cond: PushPrql<DB>,
+    Then: PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut crate::driver::Driver) {
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
         driver.push("case [");
         for (i, case) in self.cases.iter().enumerate() {
             if i > 0 {
@@ -102,13 +204,14 @@ pub struct CaseOtherwise<const N: usize, Cond, Then, Otherwise> {
     pub otherwise: Otherwise,
 }
 
-impl<const N: usize, Cond, Then, Otherwise> PushPrql for CaseOtherwise<N, Cond, Then, Otherwise>
+impl<DB: Backend, const N: usize, Cond, Then, Otherwise> PushPrql<DB>
+    for CaseOtherwise<N, Cond, Then, Otherwise>
 where
-    Cond: PushPrql,
-    Then: PushPrql,
-    Otherwise: PushPrql,
+    Cond: PushPrql<DB>,
+    Then: PushPrql<DB>,
+    Otherwise: PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut crate::driver::Driver) {
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
         driver.push("case [");
         for (i, case) in self.cases.iter().enumerate() {
             if i > 0 {
@@ -124,25 +227,363 @@ where
     }
 }
 
+/// The value of `lhs`, or of `rhs` if `lhs` is NULL.
+pub struct Coalesce<LHS, RHS> {
+    pub lhs: LHS,
+    pub rhs: RHS,
+}
+
+impl<DB: Backend, LHS, RHS> PushPrql<DB> for Coalesce<LHS, RHS>
+where
+    LHS: PushPrql<DB>,
+    RHS: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
+        driver.push('(');
+        self.lhs.push_to_driver(driver);
+        driver.push(") ?? (");
+        self.rhs.push_to_driver(driver);
+        driver.push(')');
+    }
+}
+
+/// Concatenates the text of columns. PRQL can only interpolate columns into
+/// strings, so other expressions must be derived as columns first.
+pub struct Concat<Col> {
+    pub cols: Vec<Col>,
+}
+
+impl<DB: Backend, Col> PushPrql<DB> for Concat<Col>
+where
+    Col: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
+        driver.push("f\"");
+        for col in &self.cols {
+            driver.push('{');
+            col.push_to_driver(driver);
+            driver.push('}');
+        }
+        driver.push('"');
+    }
+}
+
 pub struct Count {}
 
-impl PushPrql for Count {
-    fn push_to_driver(&self, driver: &mut crate::driver::Driver) {
+impl<DB: Backend> PushPrql<DB> for Count {
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
         driver.push("count []");
     }
 }
 
+pub struct CountDistinct<Expr> {
+    pub expr: Expr,
+}
+
+impl<DB: Backend, Expr> PushPrql<DB> for CountDistinct<Expr>
+where
+    Expr: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
+        driver.push("count_distinct ");
+        self.expr.push_to_driver(driver);
+    }
+}
+
+/// A unit of time, that dates are truncated to or extracted in, and that
+/// intervals count.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DateUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl DateUnit {
+    fn as_sql(&self) -> &'static str {
+        match self {
+            Self::Second => "second",
+            Self::Minute => "minute",
+            Self::Hour => "hour",
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+            Self::Year => "year",
+        }
+    }
+}
+
+/// Truncates a timestamp column to the start of its unit (e.g. its day). It is
+/// pushed as an s-string, which can only interpolate columns. Only Postgres has
+/// this function.
+pub struct DateTrunc<Col> {
+    pub unit: DateUnit,
+    pub col: Col,
+}
+
+impl<Col> PushPrql<Postgres> for DateTrunc<Col>
+where
+    Col: PushPrql<Postgres>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<Postgres>) {
+        driver.push("s\"date_trunc('");
+        driver.push(self.unit.as_sql());
+        driver.push("', {");
+        self.col.push_to_driver(driver);
+        driver.push("})\"");
+    }
+}
+
+/// Divides `lhs` by `rhs`. PRQL always divides as floats, even integers.
+pub struct Div<LHS, RHS> {
+    pub lhs: LHS,
+    pub rhs: RHS,
+}
+
+impl<DB: Backend, LHS, RHS> PushPrql<DB> for Div<LHS, RHS>
+where
+    LHS: PushPrql<DB>,
+    RHS: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
+        driver.push('(');
+        self.lhs.push_to_driver(driver);
+        driver.push(") / (");
+        self.rhs.push_to_driver(driver);
+        driver.push(')');
+    }
+}
+
+pub struct EndsWith<Expr, Suffix> {
+    pub expr: Expr,
+    pub suffix: Suffix,
+}
+
+impl<DB: Backend, Expr, Suffix> PushPrql<DB> for EndsWith<Expr, Suffix>
+where
+    Expr: PushPrql<DB>,
+    Suffix: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
+        driver.push("(text.ends_with (");
+        self.suffix.push_to_driver(driver);
+        driver.push(") (");
+        self.expr.push_to_driver(driver);
+        driver.push("))");
+    }
+}
+
+impl_ops!(EndsWith<Expr, Suffix>);
+
+/// The field of a timestamp column in some unit (e.g. its year), as a number.
+/// It is pushed as an s-string, which can only interpolate columns. Only
+/// Postgres has this function.
+pub struct Extract<Col> {
+    pub unit: DateUnit,
+    pub col: Col,
+}
+
+impl<Col> PushPrql<Postgres> for Extract<Col>
+where
+    Col: PushPrql<Postgres>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<Postgres>) {
+        driver.push("s\"EXTRACT(");
+        driver.push(self.unit.as_sql());
+        driver.push(" FROM {");
+        self.col.push_to_driver(driver);
+        driver.push("})\"");
+    }
+}
+
+/// A number of units of time, which can be added to or subtracted from
+/// timestamps. Intervals are literals rather than parameters, because PRQL
+/// cannot bind them. Only Postgres has intervals.
+pub struct Interval {
+    pub n: i64,
+    pub unit: DateUnit,
+}
+
+impl PushPrql<Postgres> for Interval {
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<Postgres>) {
+        driver.push('(');
+        driver.push(self.n);
+        driver.push(self.unit.as_sql());
+        driver.push("s)");
+    }
+}
+
+pub struct Length<Expr> {
+    pub expr: Expr,
+}
+
+impl<DB: Backend, Expr> PushPrql<DB> for Length<Expr>
+where
+    Expr: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
+        driver.push("(text.length (");
+        self.expr.push_to_driver(driver);
+        driver.push("))");
+    }
+}
+
+pub struct Lower<Expr> {
+    pub expr: Expr,
+}
+
+impl<DB: Backend, Expr> PushPrql<DB> for Lower<Expr>
+where
+    Expr: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
+        driver.push("(text.lower (");
+        self.expr.push_to_driver(driver);
+        driver.push("))");
+    }
+}
+
+pub struct Max<Expr> {
+    pub expr: Expr,
+}
+
+impl<DB: Backend, Expr> PushPrql<DB> for Max<Expr>
+where
+    Expr: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
+        driver.push("max ");
+        self.expr.push_to_driver(driver);
+    }
+}
+
+pub struct Min<Expr> {
+    pub expr: Expr,
+}
+
+impl<DB: Backend, Expr> PushPrql<DB> for Min<Expr>
+where
+    Expr: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
+        driver.push("min ");
+        self.expr.push_to_driver(driver);
+    }
+}
+
+pub struct Modulo<LHS, RHS> {
+    pub lhs: LHS,
+    pub rhs: RHS,
+}
+
+impl<DB: Backend, LHS, RHS> PushPrql<DB> for Modulo<LHS, RHS>
+where
+    LHS: PushPrql<DB>,
+    RHS: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
+        driver.push('(');
+        self.lhs.push_to_driver(driver);
+        driver.push(") % (");
+        self.rhs.push_to_driver(driver);
+        driver.push(')');
+    }
+}
+
+pub struct Mul<LHS, RHS> {
+    pub lhs: LHS,
+    pub rhs: RHS,
+}
+
+impl<DB: Backend, LHS, RHS> PushPrql<DB> for Mul<LHS, RHS>
+where
+    LHS: PushPrql<DB>,
+    RHS: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
+        driver.push('(');
+        self.lhs.push_to_driver(driver);
+        driver.push(") * (");
+        self.rhs.push_to_driver(driver);
+        driver.push(')');
+    }
+}
+
+pub struct Neg<Expr> {
+    pub expr: Expr,
+}
+
+impl<DB: Backend, Expr> PushPrql<DB> for Neg<Expr>
+where
+    Expr: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
+        driver.push("-(");
+        self.expr.push_to_driver(driver);
+        driver.push(')');
+    }
+}
+
+/// The current timestamp. Only Postgres has this function.
+pub struct Now {}
+
+impl PushPrql<Postgres> for Now {
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<Postgres>) {
+        driver.push("s\"now()\"");
+    }
+}
+
+pub struct StartsWith<Expr, Prefix> {
+    pub expr: Expr,
+    pub prefix: Prefix,
+}
+
+impl<DB: Backend, Expr, Prefix> PushPrql<DB> for StartsWith<Expr, Prefix>
+where
+    Expr: PushPrql<DB>,
+    Prefix: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
+        driver.push("(text.starts_with (");
+        self.prefix.push_to_driver(driver);
+        driver.push(") (");
+        self.expr.push_to_driver(driver);
+        driver.push("))");
+    }
+}
+
+impl_ops!(StartsWith<Expr, Prefix>);
+
+/// The sample standard deviation. Only Postgres has this function.
+pub struct Stddev<Expr> {
+    pub expr: Expr,
+}
+
+impl<Expr> PushPrql<Postgres> for Stddev<Expr>
+where
+    Expr: PushPrql<Postgres>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<Postgres>) {
+        driver.push("stddev ");
+        self.expr.push_to_driver(driver);
+    }
+}
+
 pub struct Sub<LHS, RHS> {
     pub lhs: LHS,
     pub rhs: RHS,
 }
 
-impl<LHS, RHS> PushPrql for Sub<LHS, RHS>
+impl<DB: Backend, LHS, RHS> PushPrql<DB> for Sub<LHS, RHS>
 where
-    LHS: PushPrql,
-    RHS: PushPrql,
+    LHS: PushPrql<DB>,
+    RHS: PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut crate::driver::Driver) {
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
         self.lhs.push_to_driver(driver);
         driver.push(" - ");
         self.rhs.push_to_driver(driver);
@@ -153,16 +594,31 @@ pub struct Sum<Expr> {
     pub expr: Expr,
 }
 
-impl<Expr> PushPrql for Sum<Expr>
+impl<DB: Backend, Expr> PushPrql<DB> for Sum<Expr>
 where
-    Expr: PushPrql,
+    Expr: PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut crate::driver::Driver) {
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
         driver.push("sum ");
         self.expr.push_to_driver(driver);
     }
 }
 
+pub struct Upper<Expr> {
+    pub expr: Expr,
+}
+
+impl<DB: Backend, Expr> PushPrql<DB> for Upper<Expr>
+where
+    Expr: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
+        driver.push("(text.upper (");
+        self.expr.push_to_driver(driver);
+        driver.push("))");
+    }
+}
+
 pub struct When<Cond> {
     pub cond: Cond,
 }
@@ -181,14 +637,97 @@ pub struct WhenThen<Cond, Then> {
     pub then: Then,
 }
 
-impl<Cond, Then> PushPrql for WhenThen<Cond, Then>
+impl<DB: Backend, Cond, Then> PushPrql<DB> for WhenThen<Cond, Then>
 where
-    Cond: PushPrql,
-    Then: PushPrql,
+    Cond: PushPrql<DB>,
+    Then: PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut crate::driver::Driver) {
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
         self.cond.push_to_driver(driver);
         driver.push(" => ");
         self.then.push_to_driver(driver);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        column::col,
+        cond::{eq, gt},
+        driver::Driver,
+        from::from,
+        table::table,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let mut driver = Driver::new();
+        {
+            from(table("orders"))
+                .derive(
+                    "total",
+                    &mul(col("price"), sub(col("quantity"), 1)) as &dyn PushPrql,
+                )
+                .derive("share", &div(col("price"), col("quantity")))
+                .derive("odd", &modulo(col("quantity"), 2))
+                .derive("refund", &neg(col("price")))
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT *, price * (quantity - $1) AS total, (price * 1.0 / quantity) AS share, quantity % $2 AS odd, -price AS refund FROM orders"
+        );
+    }
+
+    #[test]
+    fn test_text() {
+        let mut driver = Driver::new();
+        {
+            from(table("users"))
+                .filter(
+                    eq(lower(col("email")), "a@lsor.dev")
+                        & starts_with(upper(col("name")), "A")
+                        & ends_with(col("email"), ".dev")
+                        & gt(length(col("name")), 3),
+                )
+                .derive(
+                    "label",
+                    &coalesce(col("nickname"), col("name")) as &dyn PushPrql,
+                )
+                .derive("full_name", &concat([col("first_name"), col("last_name")]))
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT *, COALESCE(nickname, name) AS label, CONCAT(first_name, last_name) AS full_name FROM users WHERE LOWER(email) = $1 AND UPPER(name) LIKE CONCAT($2, '%') AND email LIKE CONCAT('%', $3) AND CHAR_LENGTH(name) > $4"
+        );
+    }
+
+    #[test]
+    fn test_dates() {
+        let mut driver = Driver::new();
+        {
+            from(table("events"))
+                .filter(gt(
+                    col("created_at"),
+                    sub(now(), interval(7, DateUnit::Day)),
+                ))
+                .derive(
+                    "day",
+                    &date_trunc(DateUnit::Day, col("created_at")) as &dyn PushPrql,
+                )
+                .derive("year", &extract(DateUnit::Year, col("created_at")))
+                .derive(
+                    "expires_at",
+                    &add(col("created_at"), interval(1, DateUnit::Month)),
+                )
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT *, date_trunc('day', created_at) AS day, EXTRACT(year FROM created_at) AS year, created_at + INTERVAL '1 MONTH' AS expires_at FROM events WHERE created_at > now() - INTERVAL '7 DAY'"
+        );
+    }
+}
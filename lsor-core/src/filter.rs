@@ -1,11 +1,18 @@
+use std::ops::Range;
+
+#[cfg(feature = "graphql")]
 use async_graphql::{Enum, OneofObject};
 use chrono::{DateTime, Utc};
+use sqlx::Postgres;
 use uuid::Uuid;
 
 use crate::{
+    aggregate::{Aggregate, Group, Grouped},
+    backend::{Backend, Encodes},
     driver::{Driver, PushPrql},
+    select::{select_row, Select, SelectRow},
     sort::Sorted,
-    take::Taken,
+    take::{Taken, TakenRange},
     ColumnName, Derive,
 };
 
@@ -23,20 +30,54 @@ impl<Query, Filter> Filtered<Query, Filter> {
         Taken { query: self, n }
     }
 
+    pub fn take_range(&self, range: Range<usize>) -> TakenRange<&Self> {
+        TakenRange { query: self, range }
+    }
+
     pub fn derive<Expr>(self, name: &'static str, expr: Expr) -> Derive<Self, Expr> {
         Derive {
             query: self,
             derivations: vec![(ColumnName { name }, expr)],
         }
     }
+
+    pub fn select<Expr>(self, exprs: impl IntoIterator<Item = Expr>) -> Select<Self, Expr> {
+        Select {
+            query: self,
+            exprs: exprs.into_iter().collect(),
+        }
+    }
+
+    pub fn select_row<R>(self) -> SelectRow<Self, R> {
+        select_row(self)
+    }
+
+    pub fn aggregate<Expr>(self, name: &'static str, expr: Expr) -> Aggregate<Self, Expr> {
+        Aggregate {
+            query: self,
+            aggregations: vec![(ColumnName { name }, expr)],
+        }
+    }
+
+    pub fn group<Col, Pipeline>(
+        self,
+        columns: impl IntoIterator<Item = Col>,
+        pipeline: impl FnOnce(Grouped) -> Pipeline,
+    ) -> Group<Self, Col, Pipeline> {
+        Group {
+            query: self,
+            columns: columns.into_iter().collect(),
+            pipeline: pipeline(Grouped {}),
+        }
+    }
 }
 
-impl<Query, Filter> PushPrql for Filtered<Query, Filter>
+impl<DB: Backend, Query, Filter> PushPrql<DB> for Filtered<Query, Filter>
 where
-    Query: PushPrql,
-    Filter: PushPrql,
+    Query: PushPrql<DB>,
+    Filter: PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut Driver) {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         self.query.push_to_driver(driver);
         driver.push("\nfilter ");
         self.filter.push_to_driver(driver);
@@ -108,8 +149,60 @@ impl Filterable for bool {
     type Filter = BoolFilter;
 }
 
-#[derive(Clone, Debug, OneofObject)]
-#[graphql(rename_fields = "snake_case")]
+/// This trait is implemented by every `Filterable::Filter` and pushes the
+/// filter for some expression `lhs` to the driver. Derived filters use it to
+/// push the filters of their fields without knowing the backend.
+pub trait Filtering<DB: Backend = Postgres> {
+    fn push_filter_to_driver(&self, lhs: &dyn PushPrql<DB>, driver: &mut Driver<DB>);
+
+    fn push_json_filter_to_driver(&self, lhs: &dyn PushPrql<DB>, driver: &mut Driver<DB>);
+
+    /// Pushes the filter for the columns of the table `tn`. Only filters that
+    /// are derived for structs filter more than one column.
+    fn push_filter_to_driver_with_table_name(
+        &self,
+        tn: &dyn PushPrql<DB>,
+        driver: &mut Driver<DB>,
+    ) {
+        self.push_filter_to_driver(tn, driver)
+    }
+}
+
+macro_rules! impl_filtering {
+    ($t:ty $(, $bound:path)*) => {
+        impl<DB> Filtering<DB> for $t
+        where
+            DB: Backend $(+ $bound)*,
+        {
+            fn push_filter_to_driver(&self, lhs: &dyn PushPrql<DB>, driver: &mut Driver<DB>) {
+                self.push_to_driver(lhs, driver)
+            }
+
+            fn push_json_filter_to_driver(&self, lhs: &dyn PushPrql<DB>, driver: &mut Driver<DB>) {
+                self.push_to_driver_as_json(lhs, driver)
+            }
+        }
+    };
+}
+
+impl_filtering!(I32Filter);
+impl_filtering!(I64Filter);
+impl_filtering!(F32Filter);
+impl_filtering!(F64Filter);
+impl_filtering!(StringFilter);
+impl_filtering!(BytesFilter);
+impl_filtering!(UuidFilter);
+impl_filtering!(DateTimeFilter);
+impl_filtering!(IndexFilter, Encodes<Vec<i32>>);
+impl_filtering!(TagFilter, Encodes<Vec<String>>);
+impl_filtering!(BoolFilter);
+
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "graphql",
+    derive(OneofObject),
+    graphql(rename_fields = "snake_case")
+)]
 pub enum I32Filter {
     Eq(i32),
     Ne(i32),
@@ -120,48 +213,56 @@ pub enum I32Filter {
 }
 
 impl I32Filter {
-    pub fn push_to_driver(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver<DB: Backend>(&self, lhs: &dyn PushPrql<DB>, driver: &mut Driver<DB>) {
         match self {
             Self::Eq(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" == ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Ne(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" != ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Gt(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" > ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Ge(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" >= ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Lt(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" < ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Le(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" <= ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
         }
     }
 
-    pub fn push_to_driver_as_json(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver_as_json<DB: Backend>(
+        &self,
+        lhs: &dyn PushPrql<DB>,
+        driver: &mut Driver<DB>,
+    ) {
         self.push_to_driver(lhs, driver)
     }
 }
 
-#[derive(Clone, Debug, OneofObject)]
-#[graphql(rename_fields = "snake_case")]
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "graphql",
+    derive(OneofObject),
+    graphql(rename_fields = "snake_case")
+)]
 pub enum I64Filter {
     Eq(i64),
     Ne(i64),
@@ -172,48 +273,56 @@ pub enum I64Filter {
 }
 
 impl I64Filter {
-    pub fn push_to_driver(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver<DB: Backend>(&self, lhs: &dyn PushPrql<DB>, driver: &mut Driver<DB>) {
         match self {
             Self::Eq(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" == ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Ne(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" != ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Gt(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" > ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Ge(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" >= ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Lt(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" < ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Le(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" <= ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
         }
     }
 
-    pub fn push_to_driver_as_json(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver_as_json<DB: Backend>(
+        &self,
+        lhs: &dyn PushPrql<DB>,
+        driver: &mut Driver<DB>,
+    ) {
         self.push_to_driver(lhs, driver)
     }
 }
 
-#[derive(Clone, Debug, OneofObject)]
-#[graphql(rename_fields = "snake_case")]
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "graphql",
+    derive(OneofObject),
+    graphql(rename_fields = "snake_case")
+)]
 pub enum F32Filter {
     Eq(f32),
     Ne(f32),
@@ -224,48 +333,56 @@ pub enum F32Filter {
 }
 
 impl F32Filter {
-    pub fn push_to_driver(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver<DB: Backend>(&self, lhs: &dyn PushPrql<DB>, driver: &mut Driver<DB>) {
         match self {
             Self::Eq(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" == ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Ne(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" != ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Gt(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" > ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Ge(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" >= ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Lt(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" < ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Le(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" <= ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
         }
     }
 
-    pub fn push_to_driver_as_json(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver_as_json<DB: Backend>(
+        &self,
+        lhs: &dyn PushPrql<DB>,
+        driver: &mut Driver<DB>,
+    ) {
         self.push_to_driver(lhs, driver)
     }
 }
 
-#[derive(Clone, Debug, OneofObject)]
-#[graphql(rename_fields = "snake_case")]
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "graphql",
+    derive(OneofObject),
+    graphql(rename_fields = "snake_case")
+)]
 pub enum F64Filter {
     Eq(f64),
     Ne(f64),
@@ -276,48 +393,56 @@ pub enum F64Filter {
 }
 
 impl F64Filter {
-    pub fn push_to_driver(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver<DB: Backend>(&self, lhs: &dyn PushPrql<DB>, driver: &mut Driver<DB>) {
         match self {
             Self::Eq(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" == ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Ne(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" != ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Gt(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" > ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Ge(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" >= ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Lt(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" < ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Le(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" <= ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
         }
     }
 
-    pub fn push_to_driver_as_json(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver_as_json<DB: Backend>(
+        &self,
+        lhs: &dyn PushPrql<DB>,
+        driver: &mut Driver<DB>,
+    ) {
         self.push_to_driver(lhs, driver)
     }
 }
 
-#[derive(Clone, Debug, OneofObject)]
-#[graphql(rename_fields = "snake_case")]
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "graphql",
+    derive(OneofObject),
+    graphql(rename_fields = "snake_case")
+)]
 pub enum StringFilter {
     IsNull(bool),
     Eq(String),
@@ -331,7 +456,7 @@ pub enum StringFilter {
 }
 
 impl StringFilter {
-    pub fn push_to_driver(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver<DB: Backend>(&self, lhs: &dyn PushPrql<DB>, driver: &mut Driver<DB>) {
         match self {
             Self::IsNull(x) => {
                 lhs.push_to_driver(driver);
@@ -344,36 +469,36 @@ impl StringFilter {
             Self::Eq(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" == ");
-                driver.push_bind(x);
+                driver.push_bind(x.clone());
             }
             Self::Ne(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" != ");
-                driver.push_bind(x);
+                driver.push_bind(x.clone());
             }
             Self::Gt(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" > ");
-                driver.push_bind(x);
+                driver.push_bind(x.clone());
             }
             Self::Ge(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" >= ");
-                driver.push_bind(x);
+                driver.push_bind(x.clone());
             }
             Self::Lt(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" < ");
-                driver.push_bind(x);
+                driver.push_bind(x.clone());
             }
             Self::Le(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" <= ");
-                driver.push_bind(x);
+                driver.push_bind(x.clone());
             }
             Self::Like(x) => {
                 driver.push(" text.contains (text.lower ");
-                driver.push_bind(x);
+                driver.push_bind(x.clone());
                 driver.push(") (text.lower ");
                 lhs.push_to_driver(driver);
                 driver.push(") ");
@@ -385,14 +510,18 @@ impl StringFilter {
                     if i > 0 {
                         driver.push(", ");
                     }
-                    driver.push_bind(x);
+                    driver.push_bind(x.clone());
                 }
                 driver.push(")\"");
             }
         }
     }
 
-    pub fn push_to_driver_as_json(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver_as_json<DB: Backend>(
+        &self,
+        lhs: &dyn PushPrql<DB>,
+        driver: &mut Driver<DB>,
+    ) {
         match self {
             Self::IsNull(x) => {
                 lhs.push_to_driver(driver);
@@ -405,51 +534,55 @@ impl StringFilter {
             Self::Like(x) => {
                 lhs.push_to_driver(driver);
                 driver.push("s\"::text LIKE ");
-                driver.push_bind(x);
+                driver.push_bind(x.clone());
                 driver.push('\"');
             }
             Self::In(xs) => {
                 lhs.push_to_driver(driver);
                 driver.push(" s\"@> ");
-                driver.push_bind(sqlx::types::Json(xs));
+                driver.push_bind_json(xs);
                 driver.push('\"');
             }
             Self::Eq(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" == ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
             Self::Ne(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" != ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
             Self::Gt(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" > ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
             Self::Ge(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" >= ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
             Self::Lt(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" < ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
             Self::Le(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" <= ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
         }
     }
 }
 
-#[derive(Clone, Debug, OneofObject)]
-#[graphql(rename_fields = "snake_case")]
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "graphql",
+    derive(OneofObject),
+    graphql(rename_fields = "snake_case")
+)]
 pub enum BytesFilter {
     IsNull(bool),
     Eq(Vec<u8>),
@@ -457,7 +590,7 @@ pub enum BytesFilter {
 }
 
 impl BytesFilter {
-    pub fn push_to_driver(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver<DB: Backend>(&self, lhs: &dyn PushPrql<DB>, driver: &mut Driver<DB>) {
         match self {
             Self::IsNull(x) => {
                 lhs.push_to_driver(driver);
@@ -470,17 +603,21 @@ impl BytesFilter {
             Self::Eq(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" == ");
-                driver.push_bind(x);
+                driver.push_bind(x.clone());
             }
             Self::Ne(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" != ");
-                driver.push_bind(x);
+                driver.push_bind(x.clone());
             }
         }
     }
 
-    pub fn push_to_driver_as_json(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver_as_json<DB: Backend>(
+        &self,
+        lhs: &dyn PushPrql<DB>,
+        driver: &mut Driver<DB>,
+    ) {
         match self {
             Self::IsNull(x) => {
                 lhs.push_to_driver(driver);
@@ -493,19 +630,23 @@ impl BytesFilter {
             Self::Eq(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" == ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
             Self::Ne(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" != ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
         }
     }
 }
 
-#[derive(Clone, Debug, OneofObject)]
-#[graphql(rename_fields = "snake_case")]
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "graphql",
+    derive(OneofObject),
+    graphql(rename_fields = "snake_case")
+)]
 pub enum UuidFilter {
     IsNull(bool),
     Eq(Uuid),
@@ -514,7 +655,7 @@ pub enum UuidFilter {
 }
 
 impl UuidFilter {
-    pub fn push_to_driver(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver<DB: Backend>(&self, lhs: &dyn PushPrql<DB>, driver: &mut Driver<DB>) {
         match self {
             Self::IsNull(x) => {
                 lhs.push_to_driver(driver);
@@ -527,12 +668,12 @@ impl UuidFilter {
             Self::Eq(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" == ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Ne(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" != ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::In(xs) => {
                 lhs.push_to_driver(driver);
@@ -541,14 +682,18 @@ impl UuidFilter {
                     if i > 0 {
                         driver.push(", ");
                     }
-                    driver.push_bind(x);
+                    driver.push_bind(*x);
                 }
                 driver.push(")\"");
             }
         }
     }
 
-    pub fn push_to_driver_as_json(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver_as_json<DB: Backend>(
+        &self,
+        lhs: &dyn PushPrql<DB>,
+        driver: &mut Driver<DB>,
+    ) {
         match self {
             Self::IsNull(x) => {
                 lhs.push_to_driver(driver);
@@ -561,25 +706,29 @@ impl UuidFilter {
             Self::Eq(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" == ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
             Self::Ne(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" != ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
             Self::In(xs) => {
                 lhs.push_to_driver(driver);
                 driver.push(" s\"@> ");
-                driver.push_bind(sqlx::types::Json(xs));
+                driver.push_bind_json(xs);
                 driver.push('\"');
             }
         }
     }
 }
 
-#[derive(Clone, Debug, OneofObject)]
-#[graphql(rename_fields = "snake_case")]
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "graphql",
+    derive(OneofObject),
+    graphql(rename_fields = "snake_case")
+)]
 pub enum DateTimeFilter {
     IsNull(bool),
     Eq(DateTime<Utc>),
@@ -591,7 +740,7 @@ pub enum DateTimeFilter {
 }
 
 impl DateTimeFilter {
-    pub fn push_to_driver(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver<DB: Backend>(&self, lhs: &dyn PushPrql<DB>, driver: &mut Driver<DB>) {
         match self {
             Self::IsNull(x) => {
                 lhs.push_to_driver(driver);
@@ -604,37 +753,41 @@ impl DateTimeFilter {
             Self::Eq(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" == ");
-                driver.push_bind(x)
+                driver.push_bind(*x)
             }
             Self::Ne(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" != ");
-                driver.push_bind(x)
+                driver.push_bind(*x)
             }
             Self::Gt(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" > ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Ge(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" >= ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Lt(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" < ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
             Self::Le(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" <= ");
-                driver.push_bind(x);
+                driver.push_bind(*x);
             }
         }
     }
 
-    pub fn push_to_driver_as_json(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver_as_json<DB: Backend>(
+        &self,
+        lhs: &dyn PushPrql<DB>,
+        driver: &mut Driver<DB>,
+    ) {
         match self {
             Self::IsNull(x) => {
                 lhs.push_to_driver(driver);
@@ -647,39 +800,43 @@ impl DateTimeFilter {
             Self::Eq(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" == ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
             Self::Ne(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" != ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
             Self::Gt(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" > ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
             Self::Ge(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" >= ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
             Self::Lt(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" < ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
             Self::Le(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" <= ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
         }
     }
 }
 
-#[derive(Clone, Debug, OneofObject)]
-#[graphql(rename_fields = "snake_case")]
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "graphql",
+    derive(OneofObject),
+    graphql(rename_fields = "snake_case")
+)]
 pub enum IndexFilter {
     Gt(Vec<u32>),
     Lt(Vec<u32>),
@@ -692,7 +849,10 @@ pub enum IndexFilter {
 }
 
 impl IndexFilter {
-    pub fn push_to_driver(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver<DB>(&self, lhs: &dyn PushPrql<DB>, driver: &mut Driver<DB>)
+    where
+        DB: Backend + Encodes<Vec<i32>>,
+    {
         match self {
             Self::IsNull(x) => {
                 lhs.push_to_driver(driver);
@@ -742,7 +902,10 @@ impl IndexFilter {
         }
     }
 
-    pub fn push_to_driver_as_json(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver_as_json<DB>(&self, lhs: &dyn PushPrql<DB>, driver: &mut Driver<DB>)
+    where
+        DB: Backend + Encodes<Vec<i32>>,
+    {
         match self {
             Self::IsNull(x) => {
                 lhs.push_to_driver(driver);
@@ -755,59 +918,66 @@ impl IndexFilter {
             Self::Eq(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" == ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
             Self::Ne(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" != ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
             Self::Gt(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" > ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
             Self::Ge(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" >= ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
             Self::Lt(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" < ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
             Self::Le(x) => {
                 lhs.push_to_driver(driver);
                 driver.push(" <= ");
-                driver.push_bind(sqlx::types::Json(x));
+                driver.push_bind_json(x);
             }
             Self::In(xs) => {
                 driver.push(" s\" ");
                 lhs.push_to_driver(driver);
                 driver.push(" @> ");
-                driver.push_bind(sqlx::types::Json(xs));
+                driver.push_bind_json(xs);
                 driver.push('\"');
             }
         }
     }
 }
 
-#[derive(Clone, Debug, OneofObject)]
-#[graphql(rename_fields = "snake_case")]
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "graphql",
+    derive(OneofObject),
+    graphql(rename_fields = "snake_case")
+)]
 pub enum TagFilter {
     In(Vec<String>),
     Contains(String),
 }
 
 impl TagFilter {
-    pub fn push_to_driver(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver<DB>(&self, lhs: &dyn PushPrql<DB>, driver: &mut Driver<DB>)
+    where
+        DB: Backend + Encodes<Vec<String>>,
+    {
         match self {
             Self::In(xs) => {
                 driver.push(" s\" ");
                 lhs.push_to_driver(driver);
                 driver.push(" @> ");
-                driver.push_bind(xs);
+                driver.push_bind(xs.clone());
                 driver.push('\"');
             }
             Self::Contains(pattern) => {
@@ -815,19 +985,22 @@ impl TagFilter {
                 driver.push(" s\" EXISTS (SELECT 1 FROM unnest(");
                 lhs.push_to_driver(driver);
                 driver.push(") AS elem WHERE elem ILIKE ");
-                driver.push_bind(&format!("%{}%", pattern));
+                driver.push_bind(format!("%{}%", pattern));
                 driver.push(") \"");
             }
         }
     }
 
-    pub fn push_to_driver_as_json(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver_as_json<DB>(&self, lhs: &dyn PushPrql<DB>, driver: &mut Driver<DB>)
+    where
+        DB: Backend + Encodes<Vec<String>>,
+    {
         match self {
             Self::In(xs) => {
                 driver.push(" s\" ");
                 lhs.push_to_driver(driver);
                 driver.push(" @> ");
-                driver.push_bind(sqlx::types::Json(xs));
+                driver.push_bind_json(xs);
                 driver.push('\"');
             }
             Self::Contains(pattern) => {
@@ -835,22 +1008,26 @@ impl TagFilter {
                 driver.push(" s\" EXISTS (SELECT 1 FROM unnest(");
                 lhs.push_to_driver(driver);
                 driver.push(") AS elem WHERE elem ILIKE ");
-                driver.push_bind(&format!("%{}%", pattern));
+                driver.push_bind(format!("%{}%", pattern));
                 driver.push(") \"");
             }
         }
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Enum)]
-#[graphql(rename_items = "snake_case")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "graphql",
+    derive(Enum),
+    graphql(rename_items = "snake_case")
+)]
 pub enum BoolFilter {
     T,
     F,
 }
 
 impl BoolFilter {
-    pub fn push_to_driver(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver<DB: Backend>(&self, lhs: &dyn PushPrql<DB>, driver: &mut Driver<DB>) {
         match self {
             Self::T => {
                 lhs.push_to_driver(driver);
@@ -863,7 +1040,11 @@ impl BoolFilter {
         }
     }
 
-    pub fn push_to_driver_as_json(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+    pub fn push_to_driver_as_json<DB: Backend>(
+        &self,
+        lhs: &dyn PushPrql<DB>,
+        driver: &mut Driver<DB>,
+    ) {
         self.push_to_driver(lhs, driver)
     }
 }
@@ -1,11 +1,17 @@
+use std::ops::Range;
+
 use crate::{
+    aggregate::{Aggregate, Group, Grouped},
+    backend::Backend,
     column::ColumnName,
     derive::Derive,
     driver::{Driver, PushPrql},
     filter::Filtered,
+    join::{Join, JoinSide},
+    select::{select_row, Select, SelectRow},
     sort::Sorted,
     table::TableName,
-    take::Taken,
+    take::{Taken, TakenRange},
 };
 
 pub fn from(table: TableName) -> From {
@@ -17,6 +23,15 @@ pub struct From {
 }
 
 impl From {
+    pub fn join<On>(self, side: JoinSide, table: TableName, on: On) -> Join<Self, On> {
+        Join {
+            query: self,
+            side,
+            table,
+            on,
+        }
+    }
+
     pub fn filter<Filter>(self, filter: Filter) -> Filtered<Self, Filter> {
         Filtered {
             query: self,
@@ -32,16 +47,50 @@ impl From {
         Taken { query: self, n }
     }
 
+    pub fn take_range(self, range: Range<usize>) -> TakenRange<Self> {
+        TakenRange { query: self, range }
+    }
+
     pub fn derive<Expr>(self, name: &'static str, expr: Expr) -> Derive<Self, Expr> {
         Derive {
             query: self,
             derivations: vec![(ColumnName { name }, expr)],
         }
     }
+
+    pub fn select<Expr>(self, exprs: impl IntoIterator<Item = Expr>) -> Select<Self, Expr> {
+        Select {
+            query: self,
+            exprs: exprs.into_iter().collect(),
+        }
+    }
+
+    pub fn select_row<R>(self) -> SelectRow<Self, R> {
+        select_row(self)
+    }
+
+    pub fn aggregate<Expr>(self, name: &'static str, expr: Expr) -> Aggregate<Self, Expr> {
+        Aggregate {
+            query: self,
+            aggregations: vec![(ColumnName { name }, expr)],
+        }
+    }
+
+    pub fn group<Col, Pipeline>(
+        self,
+        columns: impl IntoIterator<Item = Col>,
+        pipeline: impl FnOnce(Grouped) -> Pipeline,
+    ) -> Group<Self, Col, Pipeline> {
+        Group {
+            query: self,
+            columns: columns.into_iter().collect(),
+            pipeline: pipeline(Grouped {}),
+        }
+    }
 }
 
-impl PushPrql for From {
-    fn push_to_driver(&self, driver: &mut Driver) {
+impl<DB: Backend> PushPrql<DB> for From {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         driver.push("from ");
         self.table.push_to_driver(driver);
     }
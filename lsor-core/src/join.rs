@@ -0,0 +1,353 @@
+use std::{marker::PhantomData, ops::Range};
+
+use sqlx::{ColumnIndex, FromRow};
+
+use crate::{
+    aggregate::{Aggregate, Group, Grouped},
+    backend::Backend,
+    column::ColumnName,
+    derive::Derive,
+    driver::{Driver, PushPrql},
+    filter::Filtered,
+    row::{FromPrefixedRow, Row},
+    select::{select_row, Select, SelectRow},
+    sort::Sorted,
+    table::{Table, TableName},
+    take::{Taken, TakenRange},
+};
+
+/// The prefixes of the columns that `SelectJoined` selects for each side of a
+/// join.
+const LHS_PREFIX: &str = "lhs__";
+const RHS_PREFIX: &str = "rhs__";
+
+/// Which rows are kept when a row on one side of a join has no match on the
+/// other side.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum JoinSide {
+    /// Keeps only the rows that match.
+    #[default]
+    Inner,
+    /// Also keeps the rows of the query that match no row of the table.
+    Left,
+    /// Also keeps the rows of the table that match no row of the query.
+    Right,
+    /// Also keeps the rows of either side that match no row of the other side.
+    Full,
+}
+
+impl JoinSide {
+    fn as_prql(&self) -> Option<&'static str> {
+        match self {
+            // inner is the default side, and is left out of the PRQL
+            Self::Inner => None,
+            Self::Left => Some("left"),
+            Self::Right => Some("right"),
+            Self::Full => Some("full"),
+        }
+    }
+}
+
+/// Joins the rows of a table to the rows of the query for which `on` is true.
+///
+/// Joined tables usually share column names (e.g. `id`), so columns must be
+/// qualified with the name of their table, either with `dot` or by using
+/// derived filters and `qualified` sorts.
+pub struct Join<Query, On> {
+    pub query: Query,
+    pub side: JoinSide,
+    pub table: TableName,
+    pub on: On,
+}
+
+impl<Query, On> Join<Query, On> {
+    pub fn join<On2>(self, side: JoinSide, table: TableName, on: On2) -> Join<Self, On2> {
+        Join {
+            query: self,
+            side,
+            table,
+            on,
+        }
+    }
+
+    pub fn filter<Filter>(self, filter: Filter) -> Filtered<Self, Filter> {
+        Filtered {
+            query: self,
+            filter,
+        }
+    }
+
+    pub fn sort<Sort>(self, sort: Sort) -> Sorted<Self, Sort> {
+        Sorted { query: self, sort }
+    }
+
+    pub fn take(self, n: usize) -> Taken<Self> {
+        Taken { query: self, n }
+    }
+
+    pub fn take_range(self, range: Range<usize>) -> TakenRange<Self> {
+        TakenRange { query: self, range }
+    }
+
+    pub fn derive<Expr>(self, name: &'static str, expr: Expr) -> Derive<Self, Expr> {
+        Derive {
+            query: self,
+            derivations: vec![(ColumnName { name }, expr)],
+        }
+    }
+
+    pub fn select<Expr>(self, exprs: impl IntoIterator<Item = Expr>) -> Select<Self, Expr> {
+        Select {
+            query: self,
+            exprs: exprs.into_iter().collect(),
+        }
+    }
+
+    pub fn select_row<R>(self) -> SelectRow<Self, R> {
+        select_row(self)
+    }
+
+    pub fn aggregate<Expr>(self, name: &'static str, expr: Expr) -> Aggregate<Self, Expr> {
+        Aggregate {
+            query: self,
+            aggregations: vec![(ColumnName { name }, expr)],
+        }
+    }
+
+    pub fn group<Col, Pipeline>(
+        self,
+        columns: impl IntoIterator<Item = Col>,
+        pipeline: impl FnOnce(Grouped) -> Pipeline,
+    ) -> Group<Self, Col, Pipeline> {
+        Group {
+            query: self,
+            columns: columns.into_iter().collect(),
+            pipeline: pipeline(Grouped {}),
+        }
+    }
+}
+
+impl<DB: Backend, Query, On> PushPrql<DB> for Join<Query, On>
+where
+    Query: PushPrql<DB>,
+    On: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        self.query.push_to_driver(driver);
+        driver.push("\njoin ");
+        if let Some(side) = self.side.as_prql() {
+            driver.push("side:");
+            driver.push(side);
+            driver.push(' ');
+        }
+        self.table.push_to_driver(driver);
+        driver.push(" (");
+        self.on.push_to_driver(driver);
+        driver.push(')');
+    }
+}
+
+/// Selects the columns of the rows `A` and `B` from a query that joins their
+/// tables, so that they can be read as `Joined<A, B>`.
+pub fn select_joined<A, B, Query>(query: Query) -> SelectJoined<Query, A, B> {
+    SelectJoined {
+        query,
+        rows: PhantomData,
+    }
+}
+
+pub struct SelectJoined<Query, A, B> {
+    pub query: Query,
+    rows: PhantomData<fn() -> (A, B)>,
+}
+
+impl<DB: Backend, Query, A, B> PushPrql<DB> for SelectJoined<Query, A, B>
+where
+    Query: PushPrql<DB>,
+    A: Row<DB> + Table,
+    B: Row<DB> + Table,
+{
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        self.query.push_to_driver(driver);
+        driver.push("\nselect {");
+        push_prefixed_columns::<DB, A>(driver, LHS_PREFIX, true);
+        push_prefixed_columns::<DB, B>(driver, RHS_PREFIX, false);
+        driver.push('}');
+    }
+}
+
+/// Pushes the columns of `R` qualified by its table, each aliased by its name
+/// with a prefix (e.g. `lhs__id = users.id`).
+fn push_prefixed_columns<DB, R>(driver: &mut Driver<DB>, prefix: &str, first: bool)
+where
+    DB: Backend,
+    R: Row<DB> + Table,
+{
+    let table_name = R::table_name();
+    for (i, column) in R::column_names().enumerate() {
+        if !first || i > 0 {
+            driver.push(", ");
+        }
+        driver.push(prefix);
+        column.name.push_to_driver(driver);
+        driver.push(" = ");
+        table_name.push_to_driver(driver);
+        driver.push('.');
+        column.name.push_to_driver(driver);
+    }
+}
+
+/// A pair of rows that are read from a query that joins their tables, and that
+/// selects their columns with `select_joined`. The row on the outer side of a
+/// join can be read as an `Option`, which is `None` when it is missing.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Joined<A, B>(pub A, pub B);
+
+impl<A, B> Joined<A, B> {
+    pub fn into_inner(self) -> (A, B) {
+        (self.0, self.1)
+    }
+}
+
+impl<A, B> From<Joined<A, B>> for (A, B) {
+    fn from(joined: Joined<A, B>) -> Self {
+        joined.into_inner()
+    }
+}
+
+impl<'r, R, A, B> FromRow<'r, R> for Joined<A, B>
+where
+    R: sqlx::Row,
+    A: FromPrefixedRow<'r, R>,
+    B: FromPrefixedRow<'r, R>,
+    for<'a> &'a str: ColumnIndex<R>,
+{
+    fn from_row(row: &'r R) -> sqlx::Result<Self> {
+        Ok(Self(
+            A::from_prefixed_row(row, LHS_PREFIX)?,
+            B::from_prefixed_row(row, RHS_PREFIX)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        column::col,
+        cond::{eq, gt},
+        from::from,
+        row::Column,
+        table::{dot, table},
+    };
+
+    use super::*;
+
+    struct User;
+
+    impl Table for User {
+        fn table_name() -> TableName {
+            table("users")
+        }
+    }
+
+    impl Row for User {
+        fn column_names() -> impl Iterator<Item = Column> {
+            [Column::new(col("id")).pk(), Column::new(col("email"))].into_iter()
+        }
+
+        fn push_column_values(&self, _driver: &mut Driver) {}
+    }
+
+    struct Token;
+
+    impl Table for Token {
+        fn table_name() -> TableName {
+            table("tokens")
+        }
+    }
+
+    impl Row for Token {
+        fn column_names() -> impl Iterator<Item = Column> {
+            [Column::new(col("id")).pk(), Column::new(col("user_id"))].into_iter()
+        }
+
+        fn push_column_values(&self, _driver: &mut Driver) {}
+    }
+
+    fn on() -> impl PushPrql {
+        eq(
+            dot(table("users"), col("id")),
+            dot(table("tokens"), col("user_id")),
+        )
+    }
+
+    #[test]
+    fn test_join() {
+        let mut driver = Driver::new();
+        {
+            from(table("users"))
+                .join(JoinSide::Inner, table("tokens"), on())
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT users.*, tokens.* FROM users JOIN tokens ON users.id = tokens.user_id"
+        );
+    }
+
+    #[test]
+    fn test_join_sides() {
+        for (side, sql) in [
+            (JoinSide::Left, "LEFT JOIN"),
+            (JoinSide::Right, "RIGHT JOIN"),
+            (JoinSide::Full, "FULL JOIN"),
+        ] {
+            let mut driver = Driver::new();
+            {
+                from(table("users"))
+                    .join(side, table("tokens"), on())
+                    .push_to_driver(&mut driver);
+            }
+            assert_eq!(
+                driver.sql(),
+                format!(
+                    "SELECT users.*, tokens.* FROM users {sql} tokens ON users.id = tokens.user_id"
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_join_filter_sort_take() {
+        let mut driver = Driver::new();
+        {
+            from(table("users"))
+                .join(JoinSide::Left, table("tokens"), on())
+                .filter(gt(dot(table("tokens"), col("id")), 10))
+                .sort(dot(table("users"), col("email")).desc())
+                .take(5)
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT users.*, tokens.* FROM users LEFT JOIN tokens ON users.id = tokens.user_id WHERE tokens.id > $1 ORDER BY users.email DESC LIMIT 5"
+        );
+    }
+
+    #[test]
+    fn test_select_joined() {
+        let mut driver = Driver::new();
+        {
+            select_joined::<User, Token, _>(from(table("users")).join(
+                JoinSide::Inner,
+                table("tokens"),
+                on(),
+            ))
+            .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT users.id AS lhs__id, users.email AS lhs__email, tokens.id AS rhs__id, tokens.user_id AS rhs__user_id FROM users JOIN tokens ON users.id = tokens.user_id"
+        );
+    }
+}
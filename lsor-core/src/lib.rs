@@ -1,4 +1,5 @@
 pub mod aggregate;
+pub mod backend;
 pub mod cache;
 pub mod column;
 pub mod cond;
@@ -6,18 +7,23 @@ pub mod cursor;
 pub mod derive;
 pub mod driver;
 pub mod either;
+pub mod error;
 pub mod exec;
 pub mod expr;
 pub mod filter;
 pub mod from;
+pub mod join;
 pub mod page;
 pub mod row;
+pub mod select;
 pub mod sort;
+pub mod sql;
 pub mod table;
 pub mod take;
 pub mod var;
 
 pub use aggregate::*;
+pub use backend::*;
 pub use cache::*;
 pub use column::*;
 pub use cond::*;
@@ -25,13 +31,17 @@ pub use cursor::*;
 pub use derive::*;
 pub use driver::*;
 pub use either::*;
+pub use error::*;
 pub use exec::*;
 pub use expr::*;
 pub use filter::*;
 pub use from::*;
+pub use join::*;
 pub use page::*;
 pub use row::*;
+pub use select::*;
 pub use sort::*;
+pub use sql::*;
 pub use table::*;
 pub use take::*;
 pub use var::*;
@@ -1,126 +1,292 @@
-use async_graphql::SimpleObject;
+#[cfg(feature = "graphql")]
+use async_graphql::{
+    connection::{Connection, Edge},
+    Context, OutputType, SimpleObject,
+};
 use serde::{Deserialize, Serialize};
 
+use std::ops::Range;
+
 use crate::{
+    aggregate::Aggregate,
+    backend::Backend,
     column::col,
-    cond::{and, gt, lt},
-    cursor::Cursor,
+    cond::gt,
+    cursor::{CompositeCursor, Cursor, CursorSigner},
+    derive::Derive,
     derive_from,
     driver::PushPrql,
-    either::if_then_else,
+    either::Either,
+    error::CursorError,
     expr::{case, count, sum, when},
     filter::Filtered,
-    sort::SortedBy,
-    var::{one, zero},
+    sort::{Order, Sorted, SortedBy, Sorting},
+    take::Taken,
+    var::{one, zero, Literal, Var},
 };
 
 #[derive(
-    Clone,
-    Copy,
-    Debug,
-    Default,
-    Deserialize,
-    Eq,
-    Hash,
-    PartialEq,
-    PartialOrd,
-    Ord,
-    Serialize,
-    SimpleObject,
+    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize,
+)]
+#[cfg_attr(
+    feature = "graphql",
+    derive(SimpleObject),
+    graphql(rename_fields = "snake_case")
 )]
-#[graphql(rename_fields = "snake_case")]
 pub struct TotalCount {
     pub total_count: i64,
+    /// Whether `total_count` is an estimate of the query planner rather than
+    /// an exact count.
+    pub total_count_is_estimate: bool,
+}
+
+/// A page of rows loaded by `Pagination`. Unlike `Connection`, which is
+/// converted from it, it does not depend on GraphQL, e.g. for REST services.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Page<R> {
+    pub items: Vec<PageItem<R>>,
+    pub page_info: PageInfo,
+    pub total_count: i64,
+    /// Whether `total_count` is an estimate of the query planner rather than
+    /// an exact count.
+    pub total_count_is_estimate: bool,
+}
+
+/// A row of a `Page`, and the cursor that paginates from it.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct PageItem<R> {
+    pub cursor: String,
+    pub item: R,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct PageInfo {
+    pub has_previous_page: bool,
+    pub has_next_page: bool,
+    pub start_cursor: Option<String>,
+    pub end_cursor: Option<String>,
+}
+
+#[cfg(feature = "graphql")]
+impl<R: OutputType> From<Page<R>> for Connection<String, R, TotalCount> {
+    fn from(page: Page<R>) -> Self {
+        let mut conn = Connection::with_additional_fields(
+            page.page_info.has_previous_page,
+            page.page_info.has_next_page,
+            TotalCount {
+                total_count: page.total_count,
+                total_count_is_estimate: page.total_count_is_estimate,
+            },
+        );
+        conn.edges = page
+            .items
+            .into_iter()
+            .map(|item| Edge::new(item.cursor, item.item))
+            .collect();
+        conn
+    }
+}
+
+/// How the total number of rows of a paginated query is counted.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum CountStrategy {
+    /// Counts every row, which needs a scan over all of them.
+    Exact,
+    /// Does not count the rows, leaving the total count at zero.
+    #[default]
+    None,
+    /// Estimates the number of rows from the statistics of the query planner,
+    /// e.g. the row estimate of an `EXPLAIN` on Postgres. Falls back to
+    /// counting every row on backends that cannot estimate rows.
+    Estimated,
+}
+
+#[cfg(feature = "graphql")]
+impl CountStrategy {
+    /// Counts with this strategy only if the selection of a connection field
+    /// asks for its `total_count`.
+    pub fn if_selected(self, ctx: &Context<'_>) -> Self {
+        if selects_total_count(ctx) {
+            self
+        } else {
+            Self::None
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct Pagination {
+    /// The cursor of the leading sort key. The cursors of the remaining keys
+    /// are inferred from the types of their columns.
     pub cursor: Cursor,
     pub after: Option<String>,
     pub before: Option<String>,
-    pub first: usize,
-    pub last: usize,
+    /// Keeps the first `n` rows after `after` (and before `before`).
+    pub first: Option<usize>,
+    /// Keeps the last `n` rows before `before` (and after `after`). If `first`
+    /// is given as well, these are the last rows of the first `first` rows.
+    pub last: Option<usize>,
+    /// How to count the total number of rows. See `CountStrategy::if_selected`.
+    pub count: CountStrategy,
+}
+
+/// Returns whether the selection of a connection field asks for its
+/// `total_count`.
+#[cfg(feature = "graphql")]
+pub fn selects_total_count(ctx: &Context<'_>) -> bool {
+    ctx.look_ahead().field("total_count").exists()
+}
+
+impl Pagination {
+    /// Decodes the `after` and `before` cursors for a sorting with `num_keys`
+    /// keys. When a signer is given, only cursors that it has signed are
+    /// accepted.
+    pub fn decode(
+        &self,
+        signer: Option<&CursorSigner>,
+        num_keys: usize,
+    ) -> Result<DecodedPagination, CursorError> {
+        let decode = |encoded: &String| {
+            let cursor = match signer {
+                Some(signer) => signer.verify(encoded)?,
+                None => CompositeCursor::decode(encoded)?,
+            };
+            if cursor.values.len() != num_keys {
+                return Err(CursorError::Mismatched {
+                    cursor: encoded.clone(),
+                    expected: num_keys,
+                    found: cursor.values.len(),
+                });
+            }
+            Ok(cursor)
+        };
+        Ok(DecodedPagination {
+            after: self.after.as_ref().map(decode).transpose()?,
+            before: self.before.as_ref().map(decode).transpose()?,
+            first: self.first,
+            last: self.last,
+        })
+    }
+}
+
+/// Pagination by page number, for when clients need to jump to arbitrary
+/// pages. Unlike `Pagination`, rows that are inserted or deleted while
+/// paginating shift the rows of later pages.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct OffsetPagination {
+    /// The page to load, counting from one.
+    pub page: usize,
+    pub per_page: usize,
+}
+
+impl OffsetPagination {
+    /// The positions of the rows on the page, counting from zero.
+    pub fn range(&self) -> Range<usize> {
+        let start = self.page.saturating_sub(1) * self.per_page;
+        start..start + self.per_page
+    }
+
+    /// The number of pages that `total_count` rows are spread over.
+    pub fn total_pages(&self, total_count: i64) -> usize {
+        (total_count.max(0) as usize).div_ceil(self.per_page.max(1))
+    }
+}
+
+/// A page of rows loaded by `OffsetPagination`.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct OffsetPage<R> {
+    pub items: Vec<R>,
+    /// The page that was loaded, counting from one.
+    pub page: usize,
+    pub per_page: usize,
+    pub total_pages: usize,
+    pub total_count: i64,
+}
+
+/// A `Pagination` whose cursors have been decoded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedPagination {
+    pub after: Option<CompositeCursor>,
+    pub before: Option<CompositeCursor>,
+    pub first: Option<usize>,
+    pub last: Option<usize>,
 }
 
 pub fn select_page_info<Query>(
     query: Query,
-    cursor: Cursor,
-    start: String,
-    end: String,
+    start: Option<CompositeCursor>,
+    end: Option<CompositeCursor>,
 ) -> SelectPageInfo<Query> {
-    SelectPageInfo {
-        query,
-        cursor,
-        start,
-        end,
-    }
+    SelectPageInfo { query, start, end }
 }
 
-pub fn select_page_items<Query>(query: Query, pagination: Pagination) -> SelectPageItems<Query> {
+pub fn select_page_items<Query>(
+    query: Query,
+    pagination: DecodedPagination,
+) -> SelectPageItems<Query> {
     SelectPageItems { query, pagination }
 }
 
+/// Returns the values of a cursor, ignoring it if it does not hold exactly one
+/// value for every key of the sorting.
+fn keyset_values(cursor: &Option<CompositeCursor>, num_keys: usize) -> Option<Vec<Var>> {
+    let cursor = cursor.as_ref()?;
+    if cursor.values.len() != num_keys {
+        tracing::warn!(
+            "cursor has {} values but the sorting has {} keys",
+            cursor.values.len(),
+            num_keys
+        );
+        return None;
+    }
+    Some(cursor.values.clone())
+}
+
+/// Selects whether there are rows before `start` and after `end`, in the order
+/// of the sorting, alongside the total number of rows. Both comparisons follow
+/// the order of every sort key, so this works the same for asc and desc.
 pub struct SelectPageInfo<Query> {
     pub query: Query,
-    pub cursor: Cursor,
-    pub start: String,
-    pub end: String,
+    pub start: Option<CompositeCursor>,
+    pub end: Option<CompositeCursor>,
 }
 
-impl<Query> PushPrql for SelectPageInfo<Query>
+impl<DB: Backend, Query> PushPrql<DB> for SelectPageInfo<Query>
 where
-    Query: SortedBy + PushPrql,
+    Query: SortedBy<DB> + PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut crate::driver::Driver) {
-        use super::sort::Sorting;
-
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
         let sorting = self.query.sorting();
-        let order = sorting.order();
-        let start = self.cursor.decode(&self.start);
-        let end = self.cursor.decode(&self.end);
-
-        // TODO: I believe something like this needs to be done to get asc ordering working
-        // let (start, end) = if order.is_asc() {
-        //     (
-        //         self.cursor.decode(&self.end),
-        //         self.cursor.decode(&self.start),
-        //     )
-        // } else {
-        //     (
-        //         self.cursor.decode(&self.start),
-        //         self.cursor.decode(&self.end),
-        //     )
-        // };
+        let num_keys = sorting.num_keys();
+        let start = keyset_values(&self.start, num_keys);
+        let end = keyset_values(&self.end, num_keys);
+
+        let has_prev_page = match start {
+            Some(start) => Either::Left(keyset_before(&sorting, start)),
+            None => Either::Right(Literal::Bool(false)),
+        };
+        let has_next_page = match end {
+            Some(end) => Either::Left(keyset_after(&sorting, end)),
+            None => Either::Right(Literal::Bool(false)),
+        };
 
         derive_from(
-            &self.query,
+            with_cursor_columns(&self.query, &sorting),
             vec![
-                (col("total_count"), &count() as &dyn PushPrql),
+                (col("total_count"), &count() as &dyn PushPrql<DB>),
                 (
                     col("has_prev_page"),
                     &gt(
-                        sum(case([when(if_then_else(
-                            order.is_asc(),
-                            || lt(&sorting, &start),
-                            || gt(&sorting, &start),
-                        ))
-                        .then(one())])
-                        .otherwise(zero())),
+                        sum(case([when(&has_prev_page).then(one())]).otherwise(zero())),
                         zero(),
-                    ) as &dyn PushPrql,
+                    ) as &dyn PushPrql<DB>,
                 ),
                 (
                     col("has_next_page"),
                     &gt(
-                        sum(case([when(if_then_else(
-                            order.is_asc(),
-                            || gt(&sorting, &end),
-                            || lt(&sorting, &end),
-                        ))
-                        .then(one())])
-                        .otherwise(zero())),
+                        sum(case([when(&has_next_page).then(one())]).otherwise(zero())),
                         zero(),
-                    ) as &dyn PushPrql,
+                    ) as &dyn PushPrql<DB>,
                 ),
             ],
         )
@@ -130,81 +296,499 @@ where
 
 pub struct SelectPageItems<Query> {
     pub query: Query,
-    pub pagination: Pagination,
+    pub pagination: DecodedPagination,
 }
 
-impl<Query> PushPrql for SelectPageItems<Query>
+impl<DB: Backend, Query> PushPrql<DB> for SelectPageItems<Query>
 where
-    Query: SortedBy + PushPrql,
+    Query: SortedBy<DB> + PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut crate::driver::Driver) {
-        use super::sort::Sorting;
-
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
         let sorting = self.query.sorting();
-        let order = sorting.order();
-
-        let after = self
-            .pagination
-            .after
-            .as_ref()
-            .map(|v| self.pagination.cursor.decode(v))
-            .unwrap_or(self.pagination.cursor.min());
-
-        let before = self
-            .pagination
-            .before
-            .as_ref()
-            .map(|v| self.pagination.cursor.decode(v))
-            .unwrap_or(self.pagination.cursor.max());
-
-        Filtered {
+        let num_keys = sorting.num_keys();
+
+        let after = keyset_values(&self.pagination.after, num_keys);
+        let before = keyset_values(&self.pagination.before, num_keys);
+
+        let filtered = KeysetFiltered {
+            query: with_cursor_columns(&self.query, &sorting),
+            after: after.map(|after| keyset_after(&sorting, after)),
+            before: before.map(|before| keyset_before(&sorting, before)),
+        };
+
+        // as per the Relay spec, `first` is applied before `last`, and `last`
+        // is taken from the end by flipping the sorting
+        let first = match self.pagination.first {
+            Some(n) => Either::Left(filtered.take(n)),
+            None => Either::Right(&filtered),
+        };
+        let last = match self.pagination.last {
+            Some(n) => Either::Left(Taken {
+                query: Sorted {
+                    query: &first,
+                    sort: sorting.flip(),
+                },
+                n,
+            }),
+            None => Either::Right(&first),
+        };
+        Sorted {
+            query: &last,
+            sort: &sorting,
+        }
+        .push_to_driver(driver);
+    }
+}
+
+pub fn select_page<Query, S>(
+    query: Query,
+    sorting: S,
+    pagination: DecodedPagination,
+    total_count: bool,
+) -> SelectPage<Query, S> {
+    SelectPage {
+        query,
+        sorting,
+        pagination,
+        total_count,
+    }
+}
+
+/// Selects a page of rows together with its page info in a single statement.
+///
+/// One more row than asked for by `first` (or `last`) is selected, so whether
+/// there are more rows is told by trimming it off. Whether there are rows on
+/// the other side of the `after` and `before` cursors is probed with a
+/// `take 1`, and the total number of rows is only counted when asked for.
+/// These are joined onto every row of the page as the `prev_count`,
+/// `next_count` and `total_count` columns, and when the page is empty a single
+/// row without `page_item` set is selected instead.
+///
+/// The query is not sorted yet, because none of the page info needs the rows
+/// to be in order.
+pub struct SelectPage<Query, S> {
+    pub query: Query,
+    pub sorting: S,
+    pub pagination: DecodedPagination,
+    pub total_count: bool,
+}
+
+impl<DB: Backend, Query, S> PushPrql<DB> for SelectPage<Query, S>
+where
+    Query: PushPrql<DB>,
+    S: Sorting<DB>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
+        let sorting = &self.sorting;
+        let num_keys = sorting.num_keys();
+        let sorted = Sorted {
             query: &self.query,
-            filter: and(
-                if_then_else(
-                    order.is_asc(),
-                    || gt(&sorting, &after),
-                    || lt(&sorting, &after),
-                ),
-                if_then_else(
-                    order.is_asc(),
-                    || lt(&sorting, &before),
-                    || gt(&sorting, &before),
-                ),
-            ),
+            sort: sorting,
+        };
+        let unsorted = with_cursor_columns(&self.query, sorting);
+
+        let after = keyset_values(&self.pagination.after, num_keys);
+        let before = keyset_values(&self.pagination.before, num_keys);
+
+        let filtered = KeysetFiltered {
+            query: with_cursor_columns(&sorted, sorting),
+            after: after.clone().map(|after| keyset_after(&sorting, after)),
+            before: before.clone().map(|before| keyset_before(&sorting, before)),
+        };
+        // `last` is applied by the caller when `first` is given, because it
+        // has to be applied to the first `first` rows without the extra one
+        let items = match (self.pagination.first, self.pagination.last) {
+            (Some(n), _) => Either::Left(Either::Left(filtered.take(n + 1))),
+            (None, Some(n)) => Either::Left(Either::Right(Taken {
+                query: Sorted {
+                    query: &filtered,
+                    sort: sorting.flip(),
+                },
+                n: n + 1,
+            })),
+            (None, None) => Either::Right(&filtered),
+        };
+
+        driver.push("from (");
+        Derive {
+            query: items,
+            derivations: vec![(col("page_item"), Literal::Bool(true))],
         }
-        .take(self.pagination.first)
-        .sort(sorting.flip())
-        .take(self.pagination.last)
-        .sort(&sorting)
         .push_to_driver(driver);
+        driver.push(')');
+
+        // the page info is a single row, so it is right joined onto the page
+        // to still be selected when the page is empty
+        if self.total_count {
+            driver.push("\njoin side:right (");
+            Aggregate {
+                query: &self.query,
+                aggregations: vec![(col("total_count"), count())],
+            }
+            .push_to_driver(driver);
+            driver.push(") (true)");
+        }
+        let probes = [
+            (
+                "prev_count",
+                after.map(|after| keyset_after(&sorting, after)),
+            ),
+            (
+                "next_count",
+                before.map(|before| keyset_before(&sorting, before)),
+            ),
+        ];
+        for (name, cmp) in probes {
+            let Some(cmp) = cmp else {
+                continue;
+            };
+            driver.push("\njoin side:right (");
+            // counting the rows directly lets prqlc select no columns at all
+            Aggregate {
+                query: Derive {
+                    query: Taken {
+                        query: Filtered {
+                            query: &unsorted,
+                            filter: NotBeyond(&cmp),
+                        },
+                        n: 1,
+                    },
+                    derivations: vec![(col("page_probe"), one())],
+                },
+                aggregations: vec![(col(name), sum(col("page_probe")))],
+            }
+            .push_to_driver(driver);
+            driver.push(") (true)");
+        }
+
+        // joins do not keep the order of the page
+        if num_keys > 0 {
+            driver.push("\nsort {");
+            for i in 0..num_keys {
+                if i > 0 {
+                    driver.push(", ");
+                }
+                if !sorting.order_of_key(i).is_asc() {
+                    driver.push('-');
+                }
+                CursorColumn(i).push_to_driver(driver);
+            }
+            driver.push('}');
+        }
+    }
+}
+
+/// Keeps the rows that do not come strictly after (or before) a cursor. A row
+/// that is compared with NULL does not come after the cursor, so the unknown
+/// comparison is coalesced into `false` before it is negated.
+struct NotBeyond<'a, S>(&'a KeysetCmp<S>);
+
+impl<DB: Backend, S> PushPrql<DB> for NotBeyond<'_, S>
+where
+    S: Sorting<DB>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
+        driver.push("!((");
+        self.0.push_to_driver(driver);
+        driver.push(") ?? false)");
+    }
+}
+
+/// The column that the `i`th key of a sorting is derived into while
+/// paginating. The values of these columns make up the cursor of a row.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct CursorColumn(pub usize);
+
+impl CursorColumn {
+    pub fn name(&self) -> String {
+        format!("cursor_{}", self.0)
+    }
+}
+
+impl<DB: Backend> PushPrql<DB> for CursorColumn {
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
+        driver.push("cursor_");
+        driver.push(self.0);
+    }
+}
+
+pub fn with_cursor_columns<Query, S>(query: Query, sorting: S) -> WithCursorColumns<Query, S> {
+    WithCursorColumns { query, sorting }
+}
+
+/// Derives a `CursorColumn` for every key of a sorting.
+pub struct WithCursorColumns<Query, S> {
+    pub query: Query,
+    pub sorting: S,
+}
+
+impl<DB: Backend, Query, S> PushPrql<DB> for WithCursorColumns<Query, S>
+where
+    Query: PushPrql<DB>,
+    S: Sorting<DB>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
+        self.query.push_to_driver(driver);
+        if self.sorting.num_keys() == 0 {
+            return;
+        }
+        driver.push("\nderive {");
+        for i in 0..self.sorting.num_keys() {
+            if i > 0 {
+                driver.push(',');
+            }
+            driver.push(' ');
+            CursorColumn(i).push_to_driver(driver);
+            driver.push(" = ");
+            self.sorting.push_key_to_driver(i, driver);
+        }
+        driver.push(" }");
+    }
+}
+
+/// Keeps the rows that come strictly after `after`, and strictly before
+/// `before`, omitting the filter for absent bounds.
+pub struct KeysetFiltered<Query, S> {
+    pub query: Query,
+    pub after: Option<KeysetCmp<S>>,
+    pub before: Option<KeysetCmp<S>>,
+}
+
+impl<Query, S> KeysetFiltered<Query, S> {
+    pub fn take(&self, n: usize) -> Taken<&Self> {
+        Taken { query: self, n }
+    }
+}
+
+impl<DB: Backend, Query, S> PushPrql<DB> for KeysetFiltered<Query, S>
+where
+    Query: PushPrql<DB>,
+    S: Sorting<DB>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
+        self.query.push_to_driver(driver);
+        for (i, cmp) in self.after.iter().chain(self.before.iter()).enumerate() {
+            driver.push(if i == 0 { "\nfilter " } else { " && " });
+            cmp.push_to_driver(driver);
+        }
+    }
+}
+
+pub fn keyset_after<S>(sorting: S, values: Vec<Var>) -> KeysetCmp<S> {
+    KeysetCmp {
+        sorting,
+        values,
+        after: true,
+    }
+}
+
+pub fn keyset_before<S>(sorting: S, values: Vec<Var>) -> KeysetCmp<S> {
+    KeysetCmp {
+        sorting,
+        values,
+        after: false,
+    }
+}
+
+/// Compares the `CursorColumn`s of a sorting with the values of a cursor,
+/// keeping the rows that come strictly after (or before) the cursor in the
+/// order of the sorting.
+///
+/// Consecutive keys with the same order that can never be NULL are compared as
+/// a row value, e.g. `(cursor_0, cursor_1) > ($1, $2)`. Every other key is
+/// compared on its own, e.g. `(cursor_0 > $1) || (cursor_0 == $2 && ...)`,
+/// which also places NULLs where the backend sorts them.
+pub struct KeysetCmp<S> {
+    pub sorting: S,
+    pub values: Vec<Var>,
+    pub after: bool,
+}
+
+/// A run of consecutive keys that are compared together.
+struct KeyGroup {
+    keys: Range<usize>,
+    order: Order,
+    nullable: bool,
+}
+
+impl<S> KeysetCmp<S> {
+    /// Splits the keys into runs of consecutive non-nullable keys with the same
+    /// order. Nullable keys are always in a group of their own.
+    fn groups<DB: Backend>(&self) -> Vec<KeyGroup>
+    where
+        S: Sorting<DB>,
+    {
+        let mut groups: Vec<KeyGroup> = Vec::new();
+        for i in 0..self.values.len() {
+            let order = self.sorting.order_of_key(i);
+            let nullable = self.sorting.is_key_nullable(i) || self.values[i] == Var::Null;
+            match groups.last_mut() {
+                Some(last) if !last.nullable && !nullable && last.order == order => {
+                    last.keys.end = i + 1
+                }
+                _ => groups.push(KeyGroup {
+                    keys: i..i + 1,
+                    order,
+                    nullable,
+                }),
+            }
+        }
+        groups
+    }
+
+    /// Pushes the comparison of a group of keys that are known to not be NULL.
+    fn push_group_to_driver<DB: Backend>(
+        &self,
+        keys: Range<usize>,
+        op: &str,
+        driver: &mut crate::driver::Driver<DB>,
+    ) {
+        if keys.len() == 1 {
+            CursorColumn(keys.start).push_to_driver(driver);
+            driver.push(' ');
+            driver.push(op);
+            driver.push(' ');
+            self.values[keys.start].push_to_driver(driver);
+            return;
+        }
+
+        // PRQL has no row values, so they are written in SQL
+        driver.push("s\"(");
+        for (n, i) in keys.clone().enumerate() {
+            if n > 0 {
+                driver.push(", ");
+            }
+            driver.push('{');
+            CursorColumn(i).push_to_driver(driver);
+            driver.push('}');
+        }
+        driver.push(") ");
+        driver.push(if op == "==" { "=" } else { op });
+        driver.push(" (");
+        for (n, i) in keys.enumerate() {
+            if n > 0 {
+                driver.push(", ");
+            }
+            self.values[i].push_to_driver(driver);
+        }
+        driver.push(")\"");
+    }
+
+    /// Pushes whether a nullable key comes strictly after (or before) the
+    /// value of the cursor.
+    fn push_nullable_beyond_to_driver<DB: Backend>(
+        &self,
+        group: &KeyGroup,
+        op: &str,
+        driver: &mut crate::driver::Driver<DB>,
+    ) {
+        let i = group.keys.start;
+        // whether NULLs come after every other value, in the direction that we
+        // are comparing in
+        let nulls_beyond = (group.order.is_asc() == DB::nulls_are_largest()) == self.after;
+        match (&self.values[i], nulls_beyond) {
+            // nothing comes after NULL, except for more NULLs
+            (Var::Null, true) => driver.push("false"),
+            (Var::Null, false) => {
+                CursorColumn(i).push_to_driver(driver);
+                driver.push(" != null");
+            }
+            (_, true) => {
+                self.push_group_to_driver(group.keys.clone(), op, driver);
+                driver.push(" || ");
+                CursorColumn(i).push_to_driver(driver);
+                driver.push(" == null");
+            }
+            (_, false) => self.push_group_to_driver(group.keys.clone(), op, driver),
+        }
+    }
+
+    /// Pushes whether a nullable key is equal to the value of the cursor.
+    fn push_nullable_eq_to_driver<DB: Backend>(
+        &self,
+        group: &KeyGroup,
+        driver: &mut crate::driver::Driver<DB>,
+    ) {
+        let i = group.keys.start;
+        match &self.values[i] {
+            Var::Null => {
+                CursorColumn(i).push_to_driver(driver);
+                driver.push(" == null");
+            }
+            _ => self.push_group_to_driver(group.keys.clone(), "==", driver),
+        }
+    }
+
+    fn push_groups_to_driver<DB: Backend>(
+        &self,
+        groups: &[KeyGroup],
+        driver: &mut crate::driver::Driver<DB>,
+    ) {
+        let Some((group, rest)) = groups.split_first() else {
+            return;
+        };
+        let op = if group.order.is_asc() == self.after {
+            ">"
+        } else {
+            "<"
+        };
+
+        driver.push('(');
+        if group.nullable {
+            self.push_nullable_beyond_to_driver(group, op, driver);
+        } else {
+            self.push_group_to_driver(group.keys.clone(), op, driver);
+        }
+        if !rest.is_empty() {
+            driver.push(" || (");
+            if group.nullable {
+                self.push_nullable_eq_to_driver(group, driver);
+            } else {
+                self.push_group_to_driver(group.keys.clone(), "==", driver);
+            }
+            driver.push(" && ");
+            self.push_groups_to_driver(rest, driver);
+            driver.push(')');
+        }
+        driver.push(')');
+    }
+}
+
+impl<DB: Backend, S> PushPrql<DB> for KeysetCmp<S>
+where
+    S: Sorting<DB>,
+{
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
+        let groups = self.groups();
+        self.push_groups_to_driver(&groups, driver);
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{driver::Driver, from::from, table::table};
+    use crate::{
+        driver::Driver,
+        from::from,
+        sort::{multi_sort, not_null, then_by},
+        table::table,
+    };
+
+    fn cursor(values: Vec<Var>) -> CompositeCursor {
+        CompositeCursor::new(values)
+    }
 
     #[test]
     fn test_select_page_info() {
         let mut driver = Driver::new();
         {
             let query = from(table("page")).sort(col("created_at").asc());
-            let cursor = Cursor::String;
-            let start = "start".to_string();
-            let end = "end".to_string();
-            let select_page_info = SelectPageInfo {
-                query,
-                cursor,
-                start,
-                end,
-            };
+            let start = Some(cursor(vec![Var::String("start".to_string())]));
+            let end = Some(cursor(vec![Var::String("end".to_string())]));
+            let select_page_info = SelectPageInfo { query, start, end };
             select_page_info.push_to_driver(&mut driver);
         }
-        assert_eq!(
-            driver.sql(),
-            "SELECT *, COUNT(*) OVER (ORDER BY created_at ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING) AS total_count, SUM(CASE WHEN created_at < $1 THEN 1 ELSE 0 END) OVER (ORDER BY created_at ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING) > 0 AS has_prev_page, SUM(CASE WHEN created_at > $2 THEN 1 ELSE 0 END) OVER (ORDER BY created_at ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING) > 0 AS has_next_page FROM page ORDER BY created_at"
-        );
+        assert_eq!(driver.sql(), "SELECT *, created_at AS cursor_0, COUNT(*) OVER (ORDER BY created_at ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING) AS total_count, SUM(CASE WHEN created_at < $1 THEN 1 ELSE 0 END) OVER (ORDER BY created_at ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING) > 0 AS has_prev_page, SUM(CASE WHEN created_at > $2 OR created_at IS NULL THEN 1 ELSE 0 END) OVER (ORDER BY created_at ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING) > 0 AS has_next_page FROM page ORDER BY created_at");
     }
 
     #[test]
@@ -212,69 +796,315 @@ mod test {
         let mut driver = Driver::new();
         {
             let query = from(table("page")).sort(col("created_at").desc());
-            let cursor = Cursor::String;
-            let start = "start".to_string();
-            let end = "end".to_string();
+            let start = Some(cursor(vec![Var::String("start".to_string())]));
+            let end = Some(cursor(vec![Var::String("end".to_string())]));
+            let select_page_info = SelectPageInfo { query, start, end };
+            select_page_info.push_to_driver(&mut driver);
+        }
+        assert_eq!(driver.sql(), "SELECT *, created_at AS cursor_0, COUNT(*) OVER (ORDER BY created_at DESC ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING) AS total_count, SUM(CASE WHEN created_at > $1 OR created_at IS NULL THEN 1 ELSE 0 END) OVER (ORDER BY created_at DESC ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING) > 0 AS has_prev_page, SUM(CASE WHEN created_at < $2 THEN 1 ELSE 0 END) OVER (ORDER BY created_at DESC ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING) > 0 AS has_next_page FROM page ORDER BY created_at DESC");
+    }
+
+    #[test]
+    fn test_select_page_info_without_cursors() {
+        let mut driver = Driver::new();
+        {
+            let query = from(table("page")).sort(col("created_at").desc());
             let select_page_info = SelectPageInfo {
                 query,
-                cursor,
-                start,
-                end,
+                start: None,
+                end: None,
             };
             select_page_info.push_to_driver(&mut driver);
         }
-        assert_eq!(
-            driver.sql(),
-            "SELECT *, COUNT(*) OVER (ORDER BY created_at DESC ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING) AS total_count, SUM(CASE WHEN created_at > $1 THEN 1 ELSE 0 END) OVER (ORDER BY created_at DESC ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING) > 0 AS has_prev_page, SUM(CASE WHEN created_at < $2 THEN 1 ELSE 0 END) OVER (ORDER BY created_at DESC ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING) > 0 AS has_next_page FROM page ORDER BY created_at DESC"
-        );
+        assert_eq!(driver.sql(), "SELECT *, created_at AS cursor_0, COUNT(*) OVER (ORDER BY created_at DESC ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING) AS total_count, SUM(0) OVER (ORDER BY created_at DESC ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING) > 0 AS has_prev_page, SUM(0) OVER (ORDER BY created_at DESC ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING) > 0 AS has_next_page FROM page ORDER BY created_at DESC");
     }
 
     #[test]
     fn test_select_page_items() {
         let mut driver = Driver::new();
         {
-            let query = from(table("page"))
-                .derive("cursor", col("created_at"))
-                .sort(col("cursor").desc());
-            let cursor = Cursor::String;
-            let after = Some("after".to_string());
-            let before = Some("before".to_string());
-            let select_page_info = SelectPageItems {
+            let query = from(table("page")).sort(col("created_at").desc());
+            let after = Some(cursor(vec![Var::String("after".to_string())]));
+            let before = Some(cursor(vec![Var::String("before".to_string())]));
+            let select_page_items = SelectPageItems {
                 query,
-                pagination: Pagination {
-                    cursor,
+                pagination: DecodedPagination {
                     after,
                     before,
-                    first: 10,
-                    last: 5,
+                    first: Some(10),
+                    last: Some(5),
                 },
             };
-            select_page_info.push_to_driver(&mut driver);
+            select_page_items.push_to_driver(&mut driver);
         }
-        assert_eq!(driver.sql(), "WITH table_2 AS (SELECT *, created_at AS cursor FROM page), table_1 AS (SELECT * FROM table_2 WHERE cursor < $1 AND cursor > $2 ORDER BY cursor DESC LIMIT 10), table_0 AS (SELECT * FROM table_1 ORDER BY cursor LIMIT 5) SELECT * FROM table_0 ORDER BY cursor DESC");
+        assert_eq!(driver.sql(), "WITH table_2 AS (SELECT *, created_at AS cursor_0 FROM page), table_1 AS (SELECT * FROM table_2 WHERE cursor_0 < $1 AND (cursor_0 > $2 OR cursor_0 IS NULL) ORDER BY created_at DESC LIMIT 10), table_0 AS (SELECT * FROM table_1 ORDER BY created_at LIMIT 5) SELECT * FROM table_0 ORDER BY created_at DESC");
     }
 
     #[test]
-    fn test_select_page_items_with_dangling_cursor() {
+    fn test_select_page_items_without_cursors() {
         let mut driver = Driver::new();
         {
             let query = from(table("page")).sort(col("created_at").desc());
-            let query = query.derive("cursor", col("created_at"));
-            let cursor = Cursor::String;
-            let after = Some("after".to_string());
-            let before = Some("before".to_string());
-            let select_page_info = SelectPageItems {
+            let select_page_items = SelectPageItems {
+                query,
+                pagination: DecodedPagination {
+                    after: None,
+                    before: None,
+                    first: Some(10),
+                    last: Some(5),
+                },
+            };
+            select_page_items.push_to_driver(&mut driver);
+        }
+        assert_eq!(driver.sql(), "WITH table_1 AS (SELECT *, created_at AS cursor_0 FROM page ORDER BY created_at DESC LIMIT 10), table_0 AS (SELECT * FROM table_1 ORDER BY created_at LIMIT 5) SELECT * FROM table_0 ORDER BY created_at DESC");
+    }
+
+    #[test]
+    fn test_select_page_items_first() {
+        let mut driver = Driver::new();
+        {
+            let query = from(table("page")).sort(col("created_at").asc());
+            let select_page_items = SelectPageItems {
+                query,
+                pagination: DecodedPagination {
+                    after: None,
+                    before: None,
+                    first: Some(10),
+                    last: None,
+                },
+            };
+            select_page_items.push_to_driver(&mut driver);
+        }
+        assert_eq!(driver.sql(), "WITH table_0 AS (SELECT *, created_at AS cursor_0 FROM page ORDER BY created_at LIMIT 10) SELECT * FROM table_0 ORDER BY created_at");
+    }
+
+    #[test]
+    fn test_select_page_items_last() {
+        let mut driver = Driver::new();
+        {
+            let query = from(table("page")).sort(col("created_at").asc());
+            let select_page_items = SelectPageItems {
+                query,
+                pagination: DecodedPagination {
+                    after: None,
+                    before: None,
+                    first: None,
+                    last: Some(5),
+                },
+            };
+            select_page_items.push_to_driver(&mut driver);
+        }
+        assert_eq!(driver.sql(), "WITH table_0 AS (SELECT *, created_at AS cursor_0 FROM page ORDER BY created_at DESC LIMIT 5) SELECT * FROM table_0 ORDER BY created_at");
+    }
+
+    #[test]
+    fn test_select_page_items_with_composite_cursor() {
+        let mut driver = Driver::new();
+        {
+            let query = from(table("page"))
+                .sort(not_null(then_by(col("created_at").asc(), col("id").asc())));
+            let after = Some(cursor(vec![Var::String("after".to_string()), Var::I64(1)]));
+            let select_page_items = SelectPageItems {
                 query,
-                pagination: Pagination {
-                    cursor,
+                pagination: DecodedPagination {
                     after,
+                    before: None,
+                    first: Some(10),
+                    last: Some(5),
+                },
+            };
+            select_page_items.push_to_driver(&mut driver);
+        }
+        assert_eq!(driver.sql(), "WITH table_2 AS (SELECT *, created_at AS cursor_0, id AS cursor_1 FROM page), table_1 AS (SELECT * FROM table_2 WHERE (cursor_0, cursor_1) > ($1, $2) ORDER BY created_at, id LIMIT 10), table_0 AS (SELECT * FROM table_1 ORDER BY created_at DESC, id DESC LIMIT 5) SELECT * FROM table_0 ORDER BY created_at, id");
+    }
+
+    #[test]
+    fn test_select_page_items_with_mixed_orders() {
+        let mut driver = Driver::new();
+        {
+            let query = from(table("page")).sort(then_by(
+                col("status").asc(),
+                not_null(multi_sort(vec![col("created_at").desc(), col("id").desc()])),
+            ));
+            let before = Some(cursor(vec![
+                Var::String("active".to_string()),
+                Var::String("before".to_string()),
+                Var::I64(1),
+            ]));
+            let select_page_items = SelectPageItems {
+                query,
+                pagination: DecodedPagination {
+                    after: None,
                     before,
-                    first: 10,
-                    last: 5,
+                    first: Some(10),
+                    last: Some(5),
                 },
             };
-            select_page_info.push_to_driver(&mut driver);
+            select_page_items.push_to_driver(&mut driver);
         }
-        assert_eq!(driver.sql(), "WITH table_2 AS (SELECT *, created_at AS cursor FROM page), table_1 AS (SELECT * FROM table_2 WHERE created_at < $1 AND created_at > $2 ORDER BY created_at DESC LIMIT 10), table_0 AS (SELECT * FROM table_1 ORDER BY created_at LIMIT 5) SELECT * FROM table_0 ORDER BY created_at DESC");
+        assert_eq!(driver.sql(), "WITH table_2 AS (SELECT *, status AS cursor_0, created_at AS cursor_1, id AS cursor_2 FROM page), table_1 AS (SELECT * FROM table_2 WHERE cursor_0 < $1 OR cursor_0 = $2 AND (cursor_1, cursor_2) > ($3, $4) ORDER BY status, created_at DESC, id DESC LIMIT 10), table_0 AS (SELECT * FROM table_1 ORDER BY status DESC, created_at, id LIMIT 5) SELECT * FROM table_0 ORDER BY status, created_at DESC, id DESC");
+    }
+
+    #[test]
+    fn test_select_page_items_with_mismatched_cursor() {
+        let mut driver = Driver::new();
+        {
+            let query = from(table("page")).sort(then_by(col("created_at").asc(), col("id").asc()));
+            let after = Some(cursor(vec![Var::String("after".to_string())]));
+            let select_page_items = SelectPageItems {
+                query,
+                pagination: DecodedPagination {
+                    after,
+                    before: None,
+                    first: Some(10),
+                    last: Some(5),
+                },
+            };
+            select_page_items.push_to_driver(&mut driver);
+        }
+        assert_eq!(driver.sql(), "WITH table_1 AS (SELECT *, created_at AS cursor_0, id AS cursor_1 FROM page ORDER BY created_at, id LIMIT 10), table_0 AS (SELECT * FROM table_1 ORDER BY created_at DESC, id DESC LIMIT 5) SELECT * FROM table_0 ORDER BY created_at, id");
+    }
+
+    #[test]
+    fn test_select_page_items_with_null_cursor() {
+        let mut driver = Driver::new();
+        {
+            let query = from(table("page"))
+                .sort(then_by(col("deleted_at").asc(), not_null(col("id").asc())));
+            let after = Some(cursor(vec![Var::Null, Var::I64(1)]));
+            let before = Some(cursor(vec![Var::Null, Var::I64(9)]));
+            let select_page_items = SelectPageItems {
+                query,
+                pagination: DecodedPagination {
+                    after,
+                    before,
+                    first: Some(10),
+                    last: None,
+                },
+            };
+            select_page_items.push_to_driver(&mut driver);
+        }
+        assert_eq!(driver.sql(), "WITH table_1 AS (SELECT *, deleted_at AS cursor_0, id AS cursor_1 FROM page), table_0 AS (SELECT * FROM table_1 WHERE (false OR cursor_0 IS NULL AND cursor_1 > $1) AND (cursor_0 IS NOT NULL OR cursor_0 IS NULL AND cursor_1 < $2) ORDER BY deleted_at, id LIMIT 10) SELECT * FROM table_0 ORDER BY deleted_at, id");
+    }
+
+    #[test]
+    fn test_select_page() {
+        let mut driver = Driver::new();
+        {
+            let query = from(table("page"));
+            let sorting = not_null(then_by(col("created_at").desc(), col("id").desc()));
+            let select_page = SelectPage {
+                query,
+                sorting,
+                pagination: DecodedPagination {
+                    after: None,
+                    before: None,
+                    first: Some(10),
+                    last: None,
+                },
+                total_count: false,
+            };
+            select_page.push_to_driver(&mut driver);
+        }
+        assert_eq!(driver.sql(), "WITH table_0 AS (SELECT *, created_at AS cursor_0, id AS cursor_1, true AS page_item FROM page ORDER BY created_at DESC, id DESC LIMIT 11) SELECT * FROM table_0 ORDER BY cursor_0 DESC, cursor_1 DESC");
+    }
+
+    #[test]
+    fn test_select_page_with_page_info() {
+        let mut driver = Driver::new();
+        {
+            let query = from(table("page"));
+            let sorting = not_null(then_by(col("created_at").asc(), col("id").asc()));
+            let after = Some(cursor(vec![Var::String("after".to_string()), Var::I64(1)]));
+            let before = Some(cursor(vec![Var::String("before".to_string()), Var::I64(2)]));
+            let select_page = SelectPage {
+                query,
+                sorting,
+                pagination: DecodedPagination {
+                    after,
+                    before,
+                    first: None,
+                    last: Some(5),
+                },
+                total_count: true,
+            };
+            select_page.push_to_driver(&mut driver);
+        }
+        assert_eq!(driver.sql(), "WITH table_4 AS (SELECT *, created_at AS cursor_0, id AS cursor_1 FROM page), table_3 AS (SELECT *, true AS page_item FROM table_4 WHERE (cursor_0, cursor_1) > ($1, $2) AND (cursor_0, cursor_1) < ($3, $4) ORDER BY created_at DESC, id DESC LIMIT 6), table_0 AS (SELECT COUNT(*) AS total_count FROM page), table_6 AS (SELECT created_at AS _expr_1, id AS _expr_2 FROM page), table_5 AS (SELECT 1 AS _expr_0 FROM table_6 WHERE NOT COALESCE((_expr_1, _expr_2) > ($5, $6), false) LIMIT 1), table_1 AS (SELECT COALESCE(SUM(_expr_0), 0) AS prev_count FROM table_5), table_8 AS (SELECT created_at AS _expr_4, id AS _expr_5 FROM page), table_7 AS (SELECT 1 AS _expr_3 FROM table_8 WHERE NOT COALESCE((_expr_4, _expr_5) < ($7, $8), false) LIMIT 1), table_2 AS (SELECT COALESCE(SUM(_expr_3), 0) AS next_count FROM table_7) SELECT table_3.*, table_0.total_count, table_1.prev_count, table_2.next_count FROM table_3 RIGHT JOIN table_0 ON true RIGHT JOIN table_1 ON true RIGHT JOIN table_2 ON true ORDER BY table_3.cursor_0, table_3.cursor_1");
+    }
+
+    #[test]
+    fn test_offset_pagination() {
+        let pagination = OffsetPagination {
+            page: 7,
+            per_page: 10,
+        };
+        assert_eq!(pagination.range(), 60..70);
+        assert_eq!(pagination.total_pages(0), 0);
+        assert_eq!(pagination.total_pages(400), 40);
+        assert_eq!(pagination.total_pages(401), 41);
+
+        let pagination = OffsetPagination {
+            page: 0,
+            per_page: 10,
+        };
+        assert_eq!(pagination.range(), 0..10);
+    }
+
+    #[test]
+    fn test_decode_pagination() {
+        let pagination = Pagination {
+            cursor: Cursor::String,
+            after: Some(cursor(vec![Var::String("after".to_string()), Var::I64(1)]).encode()),
+            before: None,
+            first: Some(10),
+            last: Some(5),
+            count: CountStrategy::None,
+        };
+        assert_eq!(
+            pagination.decode(None, 2),
+            Ok(DecodedPagination {
+                after: Some(cursor(vec![Var::String("after".to_string()), Var::I64(1)])),
+                before: None,
+                first: Some(10),
+                last: Some(5),
+            })
+        );
+        assert_eq!(
+            pagination.decode(None, 1),
+            Err(CursorError::Mismatched {
+                cursor: pagination.after.clone().unwrap(),
+                expected: 1,
+                found: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_signed_pagination() {
+        let signer = CursorSigner::new("secret");
+        let after = cursor(vec![Var::I64(1)]);
+        let pagination = Pagination {
+            cursor: Cursor::I64,
+            after: Some(signer.sign(&after)),
+            before: None,
+            first: Some(10),
+            last: Some(5),
+            count: CountStrategy::None,
+        };
+        assert_eq!(
+            pagination.decode(Some(&signer), 1).map(|p| p.after),
+            Ok(Some(after.clone()))
+        );
+
+        // unsigned cursors are rejected when signing is enabled
+        let pagination = Pagination {
+            after: Some(after.encode()),
+            ..pagination
+        };
+        assert!(matches!(
+            pagination.decode(Some(&signer), 1),
+            Err(CursorError::Malformed { .. } | CursorError::Forged { .. })
+        ));
     }
 }
@@ -1,27 +1,120 @@
+use sqlx::{postgres::PgRow, ColumnIndex, FromRow, Postgres, Row as _, ValueRef};
+
 use crate::{
+    backend::Backend,
     column::ColumnName,
     driver::{Driver, PushPrql},
+    sql::{push_columns, push_ident},
     table::{Table, TableName},
 };
 
-pub type IsPk = bool;
+/// A column of a row, and how it is written.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Column {
+    pub name: ColumnName,
+    /// Whether the column is (part of) the primary key.
+    pub pk: bool,
+    /// Whether the column is computed by the database (e.g. a generated
+    /// column) and must never be written.
+    pub readonly: bool,
+    /// Whether the column is filled by a database default (e.g. a `serial` id
+    /// or `DEFAULT now()`) and is left out of writes.
+    pub default: bool,
+}
+
+impl Column {
+    pub fn new(name: ColumnName) -> Self {
+        Self {
+            name,
+            pk: false,
+            readonly: false,
+            default: false,
+        }
+    }
+
+    pub fn pk(mut self) -> Self {
+        self.pk = true;
+        self
+    }
+
+    pub fn readonly(mut self) -> Self {
+        self.readonly = true;
+        self
+    }
+
+    pub fn default(mut self) -> Self {
+        self.default = true;
+        self
+    }
+
+    /// Whether the value of the column is pushed by `Row::push_column_values`.
+    pub fn is_written(&self) -> bool {
+        !self.readonly && !self.default
+    }
+}
+
+pub trait Row<DB: Backend = Postgres> {
+    /// All columns of the row, including the ones that are only read.
+    fn column_names() -> impl Iterator<Item = Column>;
 
-pub trait Row {
-    fn column_names() -> impl Iterator<Item = (ColumnName, IsPk)>;
-    fn push_column_values(&self, driver: &mut Driver);
+    /// Pushes the values of the written columns, in the same order as
+    /// `column_names`.
+    fn push_column_values(&self, driver: &mut Driver<DB>);
+
+    /// The unique index or constraint that upserts of this row check for
+    /// conflicts.
+    fn conflict_target() -> ConflictTarget {
+        ConflictTarget::PrimaryKey
+    }
 }
 
-impl<T> Row for &T
+impl<DB: Backend, T> Row<DB> for &T
 where
-    T: Row,
+    T: Row<DB>,
 {
-    fn column_names() -> impl Iterator<Item = (ColumnName, IsPk)> {
+    fn column_names() -> impl Iterator<Item = Column> {
         T::column_names()
     }
 
-    fn push_column_values(&self, driver: &mut Driver) {
+    fn push_column_values(&self, driver: &mut Driver<DB>) {
         (*self).push_column_values(driver)
     }
+
+    fn conflict_target() -> ConflictTarget {
+        T::conflict_target()
+    }
+}
+
+/// The names of the columns of `R` that are written.
+pub fn written_column_names<DB: Backend, R: Row<DB>>() -> impl Iterator<Item = ColumnName> {
+    R::column_names()
+        .filter(Column::is_written)
+        .map(|column| column.name)
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum ConflictTarget {
+    /// The primary key columns.
+    #[default]
+    PrimaryKey,
+    /// The columns of a unique index.
+    Columns(Vec<ColumnName>),
+    /// A named unique constraint. Only Postgres supports this.
+    Constraint(&'static str),
+}
+
+/// What an upsert does when the row conflicts with an existing row.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum UpsertMode {
+    /// Keeps the existing row.
+    DoNothing,
+    /// Updates all columns, except for the primary key and the columns of the
+    /// conflict target.
+    #[default]
+    UpdateAll,
+    /// Updates only the listed columns. Names that are not written columns of
+    /// the row (e.g. readonly or default columns) are ignored.
+    UpdateColumns(&'static [&'static str]),
 }
 
 pub fn upsert<R>(row: R) -> Upsert<R>
@@ -31,23 +124,44 @@ where
     Upsert {
         table_name: R::table_name(),
         row,
+        mode: UpsertMode::default(),
     }
 }
 
 pub fn upsert_into<R>(table_name: TableName, row: R) -> Upsert<R> {
-    Upsert { table_name, row }
+    Upsert {
+        table_name,
+        row,
+        mode: UpsertMode::default(),
+    }
 }
 
 pub struct Upsert<R> {
     pub table_name: TableName,
     pub row: R,
+    pub mode: UpsertMode,
+}
+
+impl<R> Upsert<R> {
+    pub fn mode(mut self, mode: UpsertMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Returns the upserted row as it was written, including the values of
+    /// database defaults and triggers. Only Postgres can tell inserted rows
+    /// apart from updated ones. Rows that are kept because of
+    /// `UpsertMode::DoNothing` are not returned.
+    pub fn returning(self) -> UpsertReturning<R> {
+        UpsertReturning { upsert: self }
+    }
 }
 
-impl<R> PushPrql for Upsert<R>
+impl<DB: Backend, R> PushPrql<DB> for Upsert<R>
 where
-    R: Row,
+    R: Row<DB>,
 {
-    fn push_to_driver(&self, driver: &mut Driver) {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         // TODO: For now, the PRQL compiler is unable to handle insert
         // statements. To work around this, we inject raw SQL into the driver
         // (and later - during execution - we directly execute the PRQL without
@@ -65,40 +179,213 @@ where
         driver.push("INSERT INTO ");
         self.table_name.push_to_driver(driver);
         driver.push(" (");
-        for (i, (column_name, _)) in R::column_names().enumerate() {
-            if i > 0 {
-                driver.push(", ");
-            }
-            column_name.push_to_driver(driver);
-        }
+        push_columns(driver, written_column_names::<DB, R>());
         driver.push(") VALUES (");
         self.row.push_column_values(driver);
-        driver.push(") ON CONFLICT (");
-        for (i, (column_name, _)) in R::column_names().filter(|(_, pk)| *pk).enumerate() {
-            if i > 0 {
-                driver.push(", ");
+        driver.push(")");
+        push_on_conflict::<DB, R>(driver, self.mode);
+    }
+}
+
+pub struct UpsertReturning<R> {
+    pub upsert: Upsert<R>,
+}
+
+impl<R> PushPrql<Postgres> for UpsertReturning<R>
+where
+    R: Row<Postgres>,
+{
+    fn push_to_driver(&self, driver: &mut Driver<Postgres>) {
+        self.upsert.push_to_driver(driver);
+        // `xmax` is the id of the transaction that replaced a row version, and
+        // updates are implemented as replacements, so it is only zero for rows
+        // that were inserted
+        driver.push(" RETURNING *, (xmax = 0) AS lsor_inserted");
+    }
+}
+
+/// A row returned by an upsert.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Upserted<R> {
+    pub row: R,
+    /// Whether the row was inserted rather than updated.
+    pub inserted: bool,
+}
+
+impl<'r, R> FromRow<'r, PgRow> for Upserted<R>
+where
+    R: FromRow<'r, PgRow>,
+{
+    fn from_row(row: &'r PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            row: R::from_row(row)?,
+            inserted: row.try_get("lsor_inserted")?,
+        })
+    }
+}
+
+/// Reads a row from the columns whose names start with a prefix, so that the
+/// columns of more than one row can be read from the same result (e.g. the
+/// rows of joined tables). It is derived together with `Row`.
+pub trait FromPrefixedRow<'r, R: sqlx::Row>: Sized {
+    fn from_prefixed_row(row: &'r R, prefix: &str) -> sqlx::Result<Self>;
+}
+
+impl<'r, R, T> FromPrefixedRow<'r, R> for Option<T>
+where
+    R: sqlx::Row,
+    R::Database: Backend,
+    T: FromPrefixedRow<'r, R> + Row<R::Database>,
+    for<'a> &'a str: ColumnIndex<R>,
+{
+    fn from_prefixed_row(row: &'r R, prefix: &str) -> sqlx::Result<Self> {
+        // the rows that are missing from an outer join have NULL in every
+        // column, so it is enough to check the primary key (if there is one)
+        let columns = T::column_names().collect::<Vec<_>>();
+        let keys = if columns.iter().any(|column| column.pk) {
+            columns
+                .iter()
+                .filter(|column| column.pk)
+                .collect::<Vec<_>>()
+        } else {
+            columns.iter().collect()
+        };
+        for column in keys {
+            let name = format!("{}{}", prefix, column.name.name);
+            if !row.try_get_raw(name.as_str())?.is_null() {
+                return T::from_prefixed_row(row, prefix).map(Some);
             }
-            column_name.push_to_driver(driver);
         }
-        driver.push(") DO UPDATE SET (");
-        for (i, (column_name, _)) in R::column_names().filter(|(_, pk)| !*pk).enumerate() {
+        Ok(None)
+    }
+}
+
+pub fn upsert_many<R>(rows: impl IntoIterator<Item = R>) -> UpsertMany<R>
+where
+    R: Table,
+{
+    UpsertMany {
+        table_name: R::table_name(),
+        rows: rows.into_iter().collect(),
+        mode: UpsertMode::default(),
+    }
+}
+
+pub fn upsert_many_into<R>(
+    table_name: TableName,
+    rows: impl IntoIterator<Item = R>,
+) -> UpsertMany<R> {
+    UpsertMany {
+        table_name,
+        rows: rows.into_iter().collect(),
+        mode: UpsertMode::default(),
+    }
+}
+
+/// Upserts many rows with a single `INSERT ... VALUES (...), (...)` statement.
+/// Postgres rejects a statement that upserts the same primary key twice, so
+/// the rows are expected to have distinct primary keys.
+pub struct UpsertMany<R> {
+    pub table_name: TableName,
+    pub rows: Vec<R>,
+    pub mode: UpsertMode,
+}
+
+impl<R> UpsertMany<R> {
+    pub fn mode(mut self, mode: UpsertMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Splits the rows into as few statements as possible without binding
+    /// more arguments to any of them than the backend allows.
+    pub fn chunks<DB>(&self) -> impl Iterator<Item = UpsertMany<&R>>
+    where
+        DB: Backend,
+        R: Row<DB>,
+    {
+        let num_columns = written_column_names::<DB, R>().count().max(1);
+        let rows_per_chunk = (DB::max_binds() / num_columns).max(1);
+        self.rows.chunks(rows_per_chunk).map(|rows| UpsertMany {
+            table_name: self.table_name,
+            rows: rows.iter().collect(),
+            mode: self.mode,
+        })
+    }
+}
+
+impl<DB: Backend, R> PushPrql<DB> for UpsertMany<R>
+where
+    R: Row<DB>,
+{
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        // see `Upsert` for why this pushes raw SQL
+        assert!(driver.is_empty());
+        assert!(!self.rows.is_empty(), "must upsert at least one row");
+
+        driver.push("INSERT INTO ");
+        self.table_name.push_to_driver(driver);
+        driver.push(" (");
+        push_columns(driver, written_column_names::<DB, R>());
+        driver.push(") VALUES ");
+        for (i, row) in self.rows.iter().enumerate() {
             if i > 0 {
                 driver.push(", ");
             }
-            column_name.push_to_driver(driver);
+            driver.push('(');
+            row.push_column_values(driver);
+            driver.push(')');
         }
-        driver.push(") = (");
-        for (i, (j, _)) in R::column_names()
-            .enumerate()
-            .filter(|(_, (_, pk))| !*pk)
-            .enumerate()
-        {
-            if i > 0 {
-                driver.push(", ");
-            }
-            driver.push("$");
-            driver.push(j + 1);
+        push_on_conflict::<DB, R>(driver, self.mode);
+    }
+}
+
+/// Pushes the `ON CONFLICT` clause of an upsert of `R`. The new values of the
+/// updated columns are read from `EXCLUDED`, so that the clause does not
+/// depend on the number of rows that are upserted.
+fn push_on_conflict<DB: Backend, R: Row<DB>>(driver: &mut Driver<DB>, mode: UpsertMode) {
+    let pks = <R as Row<DB>>::column_names()
+        .filter(|column| column.pk)
+        .map(|column| column.name)
+        .collect::<Vec<_>>();
+
+    driver.push(" ON CONFLICT ");
+    let target = match <R as Row<DB>>::conflict_target() {
+        ConflictTarget::PrimaryKey => pks.clone(),
+        ConflictTarget::Columns(columns) => columns,
+        ConflictTarget::Constraint(constraint) => {
+            driver.push("ON CONSTRAINT ");
+            push_ident(driver, constraint);
+            vec![]
         }
+    };
+    if !target.is_empty() {
+        driver.push("(");
+        push_columns(driver, target.iter().copied());
         driver.push(")");
     }
+
+    let updated = match mode {
+        UpsertMode::DoNothing => vec![],
+        UpsertMode::UpdateAll => written_column_names::<DB, R>()
+            .filter(|column_name| !pks.contains(column_name) && !target.contains(column_name))
+            .collect(),
+        UpsertMode::UpdateColumns(columns) => written_column_names::<DB, R>()
+            .filter(|column_name| columns.contains(&column_name.name))
+            .collect(),
+    };
+    // there is nothing to update when every column is part of the key
+    if updated.is_empty() {
+        driver.push(" DO NOTHING");
+        return;
+    }
+    driver.push(" DO UPDATE SET ");
+    for (i, column_name) in updated.into_iter().enumerate() {
+        if i > 0 {
+            driver.push(", ");
+        }
+        push_ident(driver, column_name.name);
+        driver.push(" = EXCLUDED.");
+        push_ident(driver, column_name.name);
+    }
 }
@@ -0,0 +1,224 @@
+use std::{marker::PhantomData, ops::Range};
+
+use crate::{
+    backend::Backend,
+    column::ColumnName,
+    driver::{Driver, PushPrql},
+    row::Row,
+    sort::{Sorted, SortedBy, Sorting},
+    take::{Taken, TakenRange},
+};
+
+pub fn alias<Expr>(name: &'static str, expr: Expr) -> Alias<Expr> {
+    Alias {
+        name: ColumnName { name },
+        expr,
+    }
+}
+
+/// An expression that is selected (or derived) as a column with a name.
+pub struct Alias<Expr> {
+    pub name: ColumnName,
+    pub expr: Expr,
+}
+
+impl<DB: Backend, Expr> PushPrql<DB> for Alias<Expr>
+where
+    Expr: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        self.name.push_to_driver(driver);
+        driver.push(" = ");
+        self.expr.push_to_driver(driver);
+    }
+}
+
+pub fn select_from<DB: Backend, Query>(
+    query: Query,
+    exprs: Vec<&dyn PushPrql<DB>>,
+) -> Select<Query, &dyn PushPrql<DB>> {
+    Select { query, exprs }
+}
+
+/// Keeps only the selected columns of the rows of a query. Expressions other
+/// than columns are selected with an `alias`.
+pub struct Select<Query, Expr> {
+    pub query: Query,
+    pub exprs: Vec<Expr>,
+}
+
+impl<Query, Expr> Select<Query, Expr> {
+    pub fn select(mut self, expr: Expr) -> Self {
+        self.exprs.push(expr);
+        self
+    }
+
+    pub fn sort<Sort>(self, sort: Sort) -> Sorted<Self, Sort> {
+        Sorted { query: self, sort }
+    }
+
+    pub fn take(self, n: usize) -> Taken<Self> {
+        Taken { query: self, n }
+    }
+
+    pub fn take_range(self, range: Range<usize>) -> TakenRange<Self> {
+        TakenRange { query: self, range }
+    }
+}
+
+impl<DB: Backend, Query, Expr> PushPrql<DB> for Select<Query, Expr>
+where
+    Query: PushPrql<DB>,
+    Expr: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        self.query.push_to_driver(driver);
+        driver.push("\nselect {");
+        for (i, expr) in self.exprs.iter().enumerate() {
+            if i > 0 {
+                driver.push(", ");
+            }
+            expr.push_to_driver(driver);
+        }
+        driver.push('}');
+    }
+}
+
+impl<DB: Backend, Query, Expr> SortedBy<DB> for Select<Query, Expr>
+where
+    Query: SortedBy<DB>,
+{
+    fn sorting(&self) -> impl Sorting<DB> {
+        self.query.sorting()
+    }
+}
+
+pub fn select_row<R, Query>(query: Query) -> SelectRow<Query, R> {
+    SelectRow {
+        query,
+        row: PhantomData,
+    }
+}
+
+/// Selects exactly the columns of the row `R`, so that columns that are added
+/// to its table are not fetched until they are added to `R`.
+pub struct SelectRow<Query, R> {
+    pub query: Query,
+    row: PhantomData<fn() -> R>,
+}
+
+impl<Query, R> SelectRow<Query, R> {
+    pub fn sort<Sort>(self, sort: Sort) -> Sorted<Self, Sort> {
+        Sorted { query: self, sort }
+    }
+
+    pub fn take(self, n: usize) -> Taken<Self> {
+        Taken { query: self, n }
+    }
+
+    pub fn take_range(self, range: Range<usize>) -> TakenRange<Self> {
+        TakenRange { query: self, range }
+    }
+}
+
+impl<DB: Backend, Query, R> PushPrql<DB> for SelectRow<Query, R>
+where
+    Query: PushPrql<DB>,
+    R: Row<DB>,
+{
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        self.query.push_to_driver(driver);
+        driver.push("\nselect {");
+        for (i, column) in R::column_names().enumerate() {
+            if i > 0 {
+                driver.push(", ");
+            }
+            column.name.push_to_driver(driver);
+        }
+        driver.push('}');
+    }
+}
+
+impl<DB: Backend, Query, R> SortedBy<DB> for SelectRow<Query, R>
+where
+    Query: SortedBy<DB>,
+{
+    fn sorting(&self) -> impl Sorting<DB> {
+        self.query.sorting()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        column::col,
+        cond::gt,
+        expr::add,
+        from::from,
+        row::Column,
+        table::{dot, table},
+    };
+
+    use super::*;
+
+    struct User;
+
+    impl Row for User {
+        fn column_names() -> impl Iterator<Item = Column> {
+            [
+                Column::new(col("id")).pk(),
+                Column::new(col("email")),
+                Column::new(col("slug")).readonly(),
+            ]
+            .into_iter()
+        }
+
+        fn push_column_values(&self, _driver: &mut Driver) {}
+    }
+
+    #[test]
+    fn test_select() {
+        let mut driver = Driver::new();
+        {
+            from(table("users"))
+                .select([col("id"), col("email")])
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(driver.sql(), "SELECT id, email FROM users");
+    }
+
+    #[test]
+    fn test_filter_select_alias() {
+        let mut driver = Driver::new();
+        {
+            select_from(
+                from(table("users")).filter(gt(col("age"), 18)),
+                vec![
+                    &dot(table("users"), col("id")),
+                    &alias("next_age", add(col("age"), 1)),
+                ],
+            )
+            .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT id, age + $2 AS next_age FROM users WHERE age > $1"
+        );
+    }
+
+    #[test]
+    fn test_select_row() {
+        let mut driver = Driver::new();
+        {
+            from(table("users"))
+                .sort(col("email").asc())
+                .select_row::<User>()
+                .take(10)
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT id, email, slug FROM users ORDER BY email LIMIT 10"
+        );
+    }
+}
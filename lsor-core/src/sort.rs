@@ -1,53 +1,153 @@
-use async_graphql::Enum;
+#[cfg(feature = "graphql")]
+use std::borrow::Cow;
+use std::ops::Range;
+
+#[cfg(feature = "graphql")]
+use async_graphql::{
+    registry::Registry, Enum, InputType, InputValueError, InputValueResult, Value,
+};
 use chrono::{DateTime, Utc};
+use sqlx::Postgres;
 use uuid::Uuid;
 
 use crate::{
+    aggregate::{Aggregate, Group, Grouped},
+    backend::Backend,
     cursor::{Cursor, Iterable},
     driver::{Driver, PushPrql},
-    take::Taken,
+    select::{select_row, Select, SelectRow},
+    table::TableName,
+    take::{Taken, TakenRange},
     ColumnName, Derive,
 };
 
 /// The implementation of `PushPrql` must only push the expression that is being
-/// ordered by. It must not push the order itself.
-pub trait Sorting: PushPrql {
+/// ordered by. It must not push the order itself. Sortings with more than one
+/// key push (and report the order of) their first key.
+pub trait Sorting<DB: Backend = Postgres>: PushPrql<DB> {
     fn order(&self) -> Order;
-    fn flip(&self) -> impl Sorting;
-    fn push_to_driver_with_order(&self, driver: &mut Driver);
+    fn flip(&self) -> impl Sorting<DB>;
+    fn push_to_driver_with_order(&self, driver: &mut Driver<DB>);
+
+    /// The number of keys that are ordered by.
+    fn num_keys(&self) -> usize {
+        1
+    }
+
+    /// The order of the `i`th key.
+    fn order_of_key(&self, _i: usize) -> Order {
+        self.order()
+    }
+
+    /// Pushes the expression of the `i`th key, without its order.
+    fn push_key_to_driver(&self, _i: usize, driver: &mut Driver<DB>) {
+        self.push_to_driver(driver)
+    }
+
+    /// Whether the `i`th key can be NULL. Keys that can never be NULL can be
+    /// compared more efficiently while paginating.
+    fn is_key_nullable(&self, _i: usize) -> bool {
+        true
+    }
+
+    /// Like `push_to_driver`, but qualifies the columns with the table `tn`
+    /// (e.g. to tell apart the columns of joined tables). Only sortings that
+    /// are derived for structs sort by columns that they can qualify.
+    fn push_to_driver_with_table_name(&self, _tn: &dyn PushPrql<DB>, driver: &mut Driver<DB>) {
+        self.push_to_driver(driver)
+    }
+
+    /// Like `push_to_driver_with_order`, but qualifies the columns with the
+    /// table `tn`.
+    fn push_to_driver_with_order_with_table_name(
+        &self,
+        _tn: &dyn PushPrql<DB>,
+        driver: &mut Driver<DB>,
+    ) {
+        self.push_to_driver_with_order(driver)
+    }
+
+    /// Like `push_key_to_driver`, but qualifies the columns with the table
+    /// `tn`.
+    fn push_key_to_driver_with_table_name(
+        &self,
+        i: usize,
+        _tn: &dyn PushPrql<DB>,
+        driver: &mut Driver<DB>,
+    ) {
+        self.push_key_to_driver(i, driver)
+    }
 }
 
-impl<T> Sorting for &T
+impl<DB: Backend, T> Sorting<DB> for &T
 where
-    T: Sorting,
+    T: Sorting<DB>,
 {
     fn order(&self) -> Order {
         (*self).order()
     }
 
-    fn flip(&self) -> impl Sorting {
+    fn flip(&self) -> impl Sorting<DB> {
         (*self).flip()
     }
 
-    fn push_to_driver_with_order(&self, driver: &mut Driver) {
+    fn push_to_driver_with_order(&self, driver: &mut Driver<DB>) {
         (*self).push_to_driver_with_order(driver)
     }
+
+    fn num_keys(&self) -> usize {
+        (*self).num_keys()
+    }
+
+    fn order_of_key(&self, i: usize) -> Order {
+        (*self).order_of_key(i)
+    }
+
+    fn push_key_to_driver(&self, i: usize, driver: &mut Driver<DB>) {
+        (*self).push_key_to_driver(i, driver)
+    }
+
+    fn is_key_nullable(&self, i: usize) -> bool {
+        (*self).is_key_nullable(i)
+    }
+
+    fn push_to_driver_with_table_name(&self, tn: &dyn PushPrql<DB>, driver: &mut Driver<DB>) {
+        (*self).push_to_driver_with_table_name(tn, driver)
+    }
+
+    fn push_to_driver_with_order_with_table_name(
+        &self,
+        tn: &dyn PushPrql<DB>,
+        driver: &mut Driver<DB>,
+    ) {
+        (*self).push_to_driver_with_order_with_table_name(tn, driver)
+    }
+
+    fn push_key_to_driver_with_table_name(
+        &self,
+        i: usize,
+        tn: &dyn PushPrql<DB>,
+        driver: &mut Driver<DB>,
+    ) {
+        (*self).push_key_to_driver_with_table_name(i, tn, driver)
+    }
 }
 
-pub trait SortedBy {
-    fn sorting(&self) -> impl Sorting;
+pub trait SortedBy<DB: Backend = Postgres> {
+    fn sorting(&self) -> impl Sorting<DB>;
 }
 
-impl<T> SortedBy for &T
+impl<DB: Backend, T> SortedBy<DB> for &T
 where
-    T: SortedBy,
+    T: SortedBy<DB>,
 {
-    fn sorting(&self) -> impl Sorting {
+    fn sorting(&self) -> impl Sorting<DB> {
         (*self).sorting()
     }
 }
 
-#[derive(Clone, Copy, Debug, Enum, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "graphql", derive(Enum))]
 pub enum Order {
     Asc,
     Desc,
@@ -70,8 +170,8 @@ impl Order {
     }
 }
 
-impl PushPrql for Order {
-    fn push_to_driver(&self, driver: &mut Driver) {
+impl<DB: Backend> PushPrql<DB> for Order {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         if let Self::Desc = self {
             driver.push('-');
         }
@@ -83,32 +183,32 @@ pub struct Sort<By> {
     pub by: By,
 }
 
-impl<By> Sorting for Sort<By>
+impl<DB: Backend, By> Sorting<DB> for Sort<By>
 where
-    By: PushPrql,
+    By: PushPrql<DB>,
 {
     fn order(&self) -> Order {
         self.order
     }
 
-    fn flip(&self) -> impl Sorting {
+    fn flip(&self) -> impl Sorting<DB> {
         Sort {
             order: self.order.flip(),
             by: &self.by,
         }
     }
 
-    fn push_to_driver_with_order(&self, driver: &mut Driver) {
+    fn push_to_driver_with_order(&self, driver: &mut Driver<DB>) {
         self.order.push_to_driver(driver);
         self.by.push_to_driver(driver);
     }
 }
 
-impl<By> PushPrql for Sort<By>
+impl<DB: Backend, By> PushPrql<DB> for Sort<By>
 where
-    By: PushPrql,
+    By: PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut Driver) {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         self.by.push_to_driver(driver);
     }
 }
@@ -123,44 +223,511 @@ impl<Query, Sort> Sorted<Query, Sort> {
         Taken { query: self, n }
     }
 
+    pub fn take_range(&self, range: Range<usize>) -> TakenRange<&Self> {
+        TakenRange { query: self, range }
+    }
+
     pub fn derive<Expr>(&self, name: &'static str, expr: Expr) -> Derive<&Self, Expr> {
         Derive {
             query: self,
             derivations: vec![(ColumnName { name }, expr)],
         }
     }
+
+    pub fn select<Expr>(&self, exprs: impl IntoIterator<Item = Expr>) -> Select<&Self, Expr> {
+        Select {
+            query: self,
+            exprs: exprs.into_iter().collect(),
+        }
+    }
+
+    pub fn select_row<R>(&self) -> SelectRow<&Self, R> {
+        select_row(self)
+    }
+
+    pub fn aggregate<Expr>(&self, name: &'static str, expr: Expr) -> Aggregate<&Self, Expr> {
+        Aggregate {
+            query: self,
+            aggregations: vec![(ColumnName { name }, expr)],
+        }
+    }
+
+    pub fn group<Col, Pipeline>(
+        &self,
+        columns: impl IntoIterator<Item = Col>,
+        pipeline: impl FnOnce(Grouped) -> Pipeline,
+    ) -> Group<&Self, Col, Pipeline> {
+        Group {
+            query: self,
+            columns: columns.into_iter().collect(),
+            pipeline: pipeline(Grouped {}),
+        }
+    }
 }
 
-impl<Query, Sort> SortedBy for Sorted<Query, Sort>
+impl<DB: Backend, Query, Sort> SortedBy<DB> for Sorted<Query, Sort>
 where
-    Sort: Sorting,
+    Sort: Sorting<DB>,
 {
-    fn sorting(&self) -> impl Sorting {
+    fn sorting(&self) -> impl Sorting<DB> {
         &self.sort
     }
 }
 
-impl<Query, Sort> PushPrql for Sorted<Query, Sort>
+impl<DB: Backend, Query, Sort> PushPrql<DB> for Sorted<Query, Sort>
 where
-    Query: PushPrql,
-    Sort: Sorting,
+    Query: PushPrql<DB>,
+    Sort: Sorting<DB>,
 {
-    fn push_to_driver(&self, driver: &mut Driver) {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         self.query.push_to_driver(driver);
-        driver.push("\nsort { ");
-        self.sort.push_to_driver_with_order(driver);
-        driver.push(" }");
+        if self.sort.num_keys() > 0 {
+            driver.push("\nsort { ");
+            self.sort.push_to_driver_with_order(driver);
+            driver.push(" }");
+        }
     }
 }
 
-pub trait SortBy<By> {
-    fn sort_by(by: By) -> Sort<By>;
+pub fn multi_sort<S>(sorts: impl Into<Vec<S>>) -> MultiSort<S> {
+    MultiSort {
+        sorts: sorts.into(),
+    }
+}
+
+pub fn then_by<First, Then>(first: First, then: Then) -> ThenBy<First, Then> {
+    ThenBy { first, then }
+}
+
+pub fn not_null<S>(sort: S) -> NotNull<S> {
+    NotNull { sort }
+}
+
+/// Sorts by every sorting in the list, with the first one being the primary
+/// sorting. In GraphQL, this is a list of sortings (e.g. `[UserSort!]`).
+#[derive(Clone, Debug, Default)]
+pub struct MultiSort<S> {
+    pub sorts: Vec<S>,
+}
+
+impl<S> From<Vec<S>> for MultiSort<S> {
+    fn from(sorts: Vec<S>) -> Self {
+        Self { sorts }
+    }
+}
+
+impl<DB: Backend, S> Sorting<DB> for MultiSort<S>
+where
+    S: Sorting<DB>,
+{
+    fn order(&self) -> Order {
+        self.sorts
+            .iter()
+            .find(|sort| sort.num_keys() > 0)
+            .map(|sort| sort.order())
+            .unwrap_or(Order::Asc)
+    }
+
+    fn flip(&self) -> impl Sorting<DB> {
+        MultiSort {
+            sorts: self.sorts.iter().map(|sort| sort.flip()).collect(),
+        }
+    }
+
+    fn push_to_driver_with_order(&self, driver: &mut Driver<DB>) {
+        for (i, sort) in self
+            .sorts
+            .iter()
+            .filter(|sort| sort.num_keys() > 0)
+            .enumerate()
+        {
+            if i > 0 {
+                driver.push(", ");
+            }
+            sort.push_to_driver_with_order(driver);
+        }
+    }
+
+    fn num_keys(&self) -> usize {
+        self.sorts.iter().map(|sort| sort.num_keys()).sum()
+    }
+
+    fn order_of_key(&self, i: usize) -> Order {
+        let (sort, i) = self.find_key(i);
+        sort.order_of_key(i)
+    }
+
+    fn push_key_to_driver(&self, i: usize, driver: &mut Driver<DB>) {
+        let (sort, i) = self.find_key(i);
+        sort.push_key_to_driver(i, driver)
+    }
+
+    fn is_key_nullable(&self, i: usize) -> bool {
+        let (sort, i) = self.find_key(i);
+        sort.is_key_nullable(i)
+    }
+
+    fn push_to_driver_with_table_name(&self, tn: &dyn PushPrql<DB>, driver: &mut Driver<DB>) {
+        if let Some(sort) = self.sorts.iter().find(|sort| sort.num_keys() > 0) {
+            sort.push_to_driver_with_table_name(tn, driver);
+        }
+    }
+
+    fn push_to_driver_with_order_with_table_name(
+        &self,
+        tn: &dyn PushPrql<DB>,
+        driver: &mut Driver<DB>,
+    ) {
+        for (i, sort) in self
+            .sorts
+            .iter()
+            .filter(|sort| sort.num_keys() > 0)
+            .enumerate()
+        {
+            if i > 0 {
+                driver.push(", ");
+            }
+            sort.push_to_driver_with_order_with_table_name(tn, driver);
+        }
+    }
+
+    fn push_key_to_driver_with_table_name(
+        &self,
+        i: usize,
+        tn: &dyn PushPrql<DB>,
+        driver: &mut Driver<DB>,
+    ) {
+        let (sort, i) = self.find_key(i);
+        sort.push_key_to_driver_with_table_name(i, tn, driver)
+    }
+}
+
+impl<S> MultiSort<S> {
+    /// Finds the sorting that the `i`th key belongs to, and the index of the
+    /// key within that sorting.
+    fn find_key<DB: Backend>(&self, mut i: usize) -> (&S, usize)
+    where
+        S: Sorting<DB>,
+    {
+        for sort in &self.sorts {
+            if i < sort.num_keys() {
+                return (sort, i);
+            }
+            i -= sort.num_keys();
+        }
+        panic!("sort key index out of bounds")
+    }
+}
+
+impl<DB: Backend, S> PushPrql<DB> for MultiSort<S>
+where
+    S: Sorting<DB>,
+{
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        if let Some(sort) = self.sorts.iter().find(|sort| sort.num_keys() > 0) {
+            sort.push_to_driver(driver);
+        }
+    }
+}
+
+#[cfg(feature = "graphql")]
+impl<S> InputType for MultiSort<S>
+where
+    S: InputType,
+{
+    type RawValueType = Self;
+
+    fn type_name() -> Cow<'static, str> {
+        <Vec<S> as InputType>::type_name()
+    }
+
+    fn qualified_type_name() -> String {
+        <Vec<S> as InputType>::qualified_type_name()
+    }
+
+    fn create_type_info(registry: &mut Registry) -> String {
+        <Vec<S> as InputType>::create_type_info(registry)
+    }
+
+    fn parse(value: Option<Value>) -> InputValueResult<Self> {
+        <Vec<S> as InputType>::parse(value)
+            .map(Self::from)
+            .map_err(InputValueError::propagate)
+    }
+
+    fn to_value(&self) -> Value {
+        <Vec<S> as InputType>::to_value(&self.sorts)
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(self)
+    }
+}
+
+/// Sorts by `first` and breaks ties by sorting by `then`.
+#[derive(Clone, Debug)]
+pub struct ThenBy<First, Then> {
+    pub first: First,
+    pub then: Then,
+}
+
+impl<DB: Backend, First, Then> Sorting<DB> for ThenBy<First, Then>
+where
+    First: Sorting<DB>,
+    Then: Sorting<DB>,
+{
+    fn order(&self) -> Order {
+        if self.first.num_keys() > 0 {
+            self.first.order()
+        } else {
+            self.then.order()
+        }
+    }
+
+    fn flip(&self) -> impl Sorting<DB> {
+        ThenBy {
+            first: self.first.flip(),
+            then: self.then.flip(),
+        }
+    }
+
+    fn push_to_driver_with_order(&self, driver: &mut Driver<DB>) {
+        if self.first.num_keys() > 0 {
+            self.first.push_to_driver_with_order(driver);
+        }
+        if self.first.num_keys() > 0 && self.then.num_keys() > 0 {
+            driver.push(", ");
+        }
+        if self.then.num_keys() > 0 {
+            self.then.push_to_driver_with_order(driver);
+        }
+    }
+
+    fn num_keys(&self) -> usize {
+        self.first.num_keys() + self.then.num_keys()
+    }
+
+    fn order_of_key(&self, i: usize) -> Order {
+        let n = self.first.num_keys();
+        if i < n {
+            self.first.order_of_key(i)
+        } else {
+            self.then.order_of_key(i - n)
+        }
+    }
+
+    fn push_key_to_driver(&self, i: usize, driver: &mut Driver<DB>) {
+        let n = self.first.num_keys();
+        if i < n {
+            self.first.push_key_to_driver(i, driver)
+        } else {
+            self.then.push_key_to_driver(i - n, driver)
+        }
+    }
+
+    fn is_key_nullable(&self, i: usize) -> bool {
+        let n = self.first.num_keys();
+        if i < n {
+            self.first.is_key_nullable(i)
+        } else {
+            self.then.is_key_nullable(i - n)
+        }
+    }
+
+    fn push_to_driver_with_table_name(&self, tn: &dyn PushPrql<DB>, driver: &mut Driver<DB>) {
+        if self.first.num_keys() > 0 {
+            self.first.push_to_driver_with_table_name(tn, driver);
+        } else {
+            self.then.push_to_driver_with_table_name(tn, driver);
+        }
+    }
+
+    fn push_to_driver_with_order_with_table_name(
+        &self,
+        tn: &dyn PushPrql<DB>,
+        driver: &mut Driver<DB>,
+    ) {
+        if self.first.num_keys() > 0 {
+            self.first
+                .push_to_driver_with_order_with_table_name(tn, driver);
+        }
+        if self.first.num_keys() > 0 && self.then.num_keys() > 0 {
+            driver.push(", ");
+        }
+        if self.then.num_keys() > 0 {
+            self.then
+                .push_to_driver_with_order_with_table_name(tn, driver);
+        }
+    }
+
+    fn push_key_to_driver_with_table_name(
+        &self,
+        i: usize,
+        tn: &dyn PushPrql<DB>,
+        driver: &mut Driver<DB>,
+    ) {
+        let n = self.first.num_keys();
+        if i < n {
+            self.first.push_key_to_driver_with_table_name(i, tn, driver)
+        } else {
+            self.then
+                .push_key_to_driver_with_table_name(i - n, tn, driver)
+        }
+    }
+}
+
+impl<DB: Backend, First, Then> PushPrql<DB> for ThenBy<First, Then>
+where
+    First: Sorting<DB>,
+    Then: Sorting<DB>,
+{
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        if self.first.num_keys() > 0 {
+            self.first.push_to_driver(driver);
+        } else {
+            self.then.push_to_driver(driver);
+        }
+    }
 }
 
-impl<By> SortBy<By> for i32
+/// Sorts like `sort`, promising that none of its keys are ever NULL (e.g.
+/// because they are primary keys).
+#[derive(Clone, Debug)]
+pub struct NotNull<S> {
+    pub sort: S,
+}
+
+impl<DB: Backend, S> Sorting<DB> for NotNull<S>
 where
-    By: PushPrql,
+    S: Sorting<DB>,
 {
+    fn order(&self) -> Order {
+        self.sort.order()
+    }
+
+    fn flip(&self) -> impl Sorting<DB> {
+        NotNull {
+            sort: self.sort.flip(),
+        }
+    }
+
+    fn push_to_driver_with_order(&self, driver: &mut Driver<DB>) {
+        self.sort.push_to_driver_with_order(driver)
+    }
+
+    fn num_keys(&self) -> usize {
+        self.sort.num_keys()
+    }
+
+    fn order_of_key(&self, i: usize) -> Order {
+        self.sort.order_of_key(i)
+    }
+
+    fn push_key_to_driver(&self, i: usize, driver: &mut Driver<DB>) {
+        self.sort.push_key_to_driver(i, driver)
+    }
+
+    fn is_key_nullable(&self, _i: usize) -> bool {
+        false
+    }
+
+    fn push_to_driver_with_table_name(&self, tn: &dyn PushPrql<DB>, driver: &mut Driver<DB>) {
+        self.sort.push_to_driver_with_table_name(tn, driver)
+    }
+
+    fn push_to_driver_with_order_with_table_name(
+        &self,
+        tn: &dyn PushPrql<DB>,
+        driver: &mut Driver<DB>,
+    ) {
+        self.sort
+            .push_to_driver_with_order_with_table_name(tn, driver)
+    }
+
+    fn push_key_to_driver_with_table_name(
+        &self,
+        i: usize,
+        tn: &dyn PushPrql<DB>,
+        driver: &mut Driver<DB>,
+    ) {
+        self.sort.push_key_to_driver_with_table_name(i, tn, driver)
+    }
+}
+
+impl<DB: Backend, S> PushPrql<DB> for NotNull<S>
+where
+    S: Sorting<DB>,
+{
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        self.sort.push_to_driver(driver)
+    }
+}
+
+pub fn qualified<S>(table: TableName, sort: S) -> Qualified<S> {
+    Qualified { table, sort }
+}
+
+/// Sorts like `sort`, with its columns qualified by the name of `table`. This
+/// is needed to sort by the columns of joined tables.
+#[derive(Clone, Debug)]
+pub struct Qualified<S> {
+    pub table: TableName,
+    pub sort: S,
+}
+
+impl<DB: Backend, S> Sorting<DB> for Qualified<S>
+where
+    S: Sorting<DB>,
+{
+    fn order(&self) -> Order {
+        self.sort.order()
+    }
+
+    fn flip(&self) -> impl Sorting<DB> {
+        Qualified {
+            table: self.table,
+            sort: self.sort.flip(),
+        }
+    }
+
+    fn push_to_driver_with_order(&self, driver: &mut Driver<DB>) {
+        self.sort
+            .push_to_driver_with_order_with_table_name(&self.table, driver)
+    }
+
+    fn num_keys(&self) -> usize {
+        self.sort.num_keys()
+    }
+
+    fn order_of_key(&self, i: usize) -> Order {
+        self.sort.order_of_key(i)
+    }
+
+    fn push_key_to_driver(&self, i: usize, driver: &mut Driver<DB>) {
+        self.sort
+            .push_key_to_driver_with_table_name(i, &self.table, driver)
+    }
+
+    fn is_key_nullable(&self, i: usize) -> bool {
+        self.sort.is_key_nullable(i)
+    }
+}
+
+impl<DB: Backend, S> PushPrql<DB> for Qualified<S>
+where
+    S: Sorting<DB>,
+{
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        self.sort
+            .push_to_driver_with_table_name(&self.table, driver)
+    }
+}
+
+pub trait SortBy<By> {
+    fn sort_by(by: By) -> Sort<By>;
+}
+
+impl<By> SortBy<By> for i32 {
     fn sort_by(by: By) -> Sort<By> {
         Sort {
             order: Order::Asc,
@@ -169,10 +736,7 @@ where
     }
 }
 
-impl<By> SortBy<By> for u64
-where
-    By: PushPrql,
-{
+impl<By> SortBy<By> for u64 {
     fn sort_by(by: By) -> Sort<By> {
         Sort {
             order: Order::Asc,
@@ -181,10 +745,7 @@ where
     }
 }
 
-impl<By> SortBy<By> for i64
-where
-    By: PushPrql,
-{
+impl<By> SortBy<By> for i64 {
     fn sort_by(by: By) -> Sort<By> {
         Sort {
             order: Order::Asc,
@@ -193,10 +754,7 @@ where
     }
 }
 
-impl<By> SortBy<By> for f32
-where
-    By: PushPrql,
-{
+impl<By> SortBy<By> for f32 {
     fn sort_by(by: By) -> Sort<By> {
         Sort {
             order: Order::Asc,
@@ -205,10 +763,7 @@ where
     }
 }
 
-impl<By> SortBy<By> for f64
-where
-    By: PushPrql,
-{
+impl<By> SortBy<By> for f64 {
     fn sort_by(by: By) -> Sort<By> {
         Sort {
             order: Order::Asc,
@@ -217,10 +772,7 @@ where
     }
 }
 
-impl<By> SortBy<By> for String
-where
-    By: PushPrql,
-{
+impl<By> SortBy<By> for String {
     fn sort_by(by: By) -> Sort<By> {
         Sort {
             order: Order::Asc,
@@ -239,8 +791,12 @@ macro_rules! impl_sortable {
             type Sort = $i;
         }
 
-        #[derive(Clone, Copy, Debug, Enum, Eq, PartialEq)]
-        #[graphql(rename_items = "snake_case")]
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        #[cfg_attr(
+            feature = "graphql",
+            derive(Enum),
+            graphql(rename_items = "snake_case")
+        )]
         pub enum $i {
             Asc,
             Desc,
@@ -255,8 +811,8 @@ macro_rules! impl_sortable {
         impl $i {
             pub fn order(&self) -> Order {
                 match self {
-                    Self::Asc => Order::Desc,
-                    Self::Desc => Order::Asc,
+                    Self::Asc => Order::Asc,
+                    Self::Desc => Order::Desc,
                 }
             }
 
@@ -267,14 +823,18 @@ macro_rules! impl_sortable {
                 }
             }
 
-            pub fn push_to_driver_with_lhs(&self, lhs: &dyn PushPrql, driver: &mut Driver) {
+            pub fn push_to_driver_with_lhs<DB: Backend>(
+                &self,
+                lhs: &dyn PushPrql<DB>,
+                driver: &mut Driver<DB>,
+            ) {
                 lhs.push_to_driver(driver);
             }
 
-            pub fn push_to_driver_with_order_with_lhs(
+            pub fn push_to_driver_with_order_with_lhs<DB: Backend>(
                 &self,
-                lhs: &dyn PushPrql,
-                driver: &mut Driver,
+                lhs: &dyn PushPrql<DB>,
+                driver: &mut Driver<DB>,
             ) {
                 match self {
                     Self::Asc => {
@@ -386,4 +946,68 @@ mod test {
         }
         assert_eq!(driver.sql(), "WITH table_0 AS (SELECT *, info->'age' AS _expr_0 FROM users) SELECT * FROM table_0 ORDER BY _expr_0 DESC");
     }
+
+    #[test]
+    fn test_multi_sort() {
+        let mut driver = Driver::new();
+        {
+            from(table("users"))
+                .sort(multi_sort(vec![
+                    col("status").asc(),
+                    col("created_at").desc(),
+                ]))
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT * FROM users ORDER BY status, created_at DESC"
+        );
+
+        let mut driver = Driver::new();
+        {
+            from(table("users"))
+                .sort(multi_sort(vec![col("status").asc(), col("created_at").desc()]).flip())
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT * FROM users ORDER BY status DESC, created_at"
+        );
+    }
+
+    #[test]
+    fn test_multi_sort_empty() {
+        let mut driver = Driver::new();
+        {
+            from(table("users"))
+                .sort(multi_sort(Vec::<Sort<ColumnName>>::new()))
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(driver.sql(), "SELECT * FROM users");
+    }
+
+    #[test]
+    fn test_then_by() {
+        let mut driver = Driver::new();
+        {
+            from(table("users"))
+                .sort(then_by(col("age").desc(), col("id").desc()))
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT * FROM users ORDER BY age DESC, id DESC"
+        );
+
+        let mut driver = Driver::new();
+        {
+            from(table("users"))
+                .sort(then_by(
+                    multi_sort(Vec::<Sort<ColumnName>>::new()),
+                    col("id").asc(),
+                ))
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(driver.sql(), "SELECT * FROM users ORDER BY id");
+    }
 }
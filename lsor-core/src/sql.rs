@@ -0,0 +1,458 @@
+use std::marker::PhantomData;
+
+use sqlx::Postgres;
+
+use crate::{
+    backend::Backend,
+    column::ColumnName,
+    driver::{Driver, PushPrql},
+    error::{LsorError, StatementError},
+    from::from,
+    row::{written_column_names, Column, Row},
+    table::{Table, TableName},
+};
+
+/// Pushes a SQL statement into the driver, for the writes that PRQL cannot
+/// express. The driver must be empty and is executed without compilation.
+///
+/// Statements that write to filtered rows compile the filter into a subquery
+/// that selects the primary keys of the rows, which can fail.
+pub trait PushSql<DB: Backend = Postgres> {
+    fn push_sql(&self, driver: &mut Driver<DB>) -> Result<(), LsorError>;
+}
+
+pub fn insert<R>(row: R) -> Insert<R>
+where
+    R: Table,
+{
+    Insert {
+        table_name: R::table_name(),
+        row,
+    }
+}
+
+pub fn insert_into<R>(table_name: TableName, row: R) -> Insert<R> {
+    Insert { table_name, row }
+}
+
+/// Inserts a row, and fails if a row with the same primary key (or any other
+/// unique column) exists.
+pub struct Insert<R> {
+    pub table_name: TableName,
+    pub row: R,
+}
+
+impl<DB: Backend, R> PushSql<DB> for Insert<R>
+where
+    R: Row<DB>,
+{
+    fn push_sql(&self, driver: &mut Driver<DB>) -> Result<(), LsorError> {
+        ensure_empty(driver)?;
+
+        driver.push("INSERT INTO ");
+        self.table_name.push_to_driver(driver);
+        driver.push(" (");
+        push_columns(driver, written_column_names::<DB, R>());
+        driver.push(") VALUES (");
+        self.row.push_column_values(driver);
+        driver.push(")");
+        Ok(())
+    }
+}
+
+pub fn update<R>(row: R) -> Update<R>
+where
+    R: Table,
+{
+    Update {
+        table_name: R::table_name(),
+        row,
+    }
+}
+
+/// Updates all columns of the row that has the same primary key.
+pub struct Update<R> {
+    pub table_name: TableName,
+    pub row: R,
+}
+
+impl<DB: Backend, R> PushSql<DB> for Update<R>
+where
+    R: Row<DB>,
+{
+    fn push_sql(&self, driver: &mut Driver<DB>) -> Result<(), LsorError> {
+        ensure_empty(driver)?;
+
+        let table = self.table_name.name;
+        if <R as Row<DB>>::column_names().any(|column| column.pk && !column.is_written()) {
+            return Err(StatementError::MissingPrimaryKey { table }.into());
+        }
+        let columns = <R as Row<DB>>::column_names()
+            .filter(Column::is_written)
+            .zip(driver.bind_without_pushing(|driver| self.row.push_column_values(driver)))
+            .collect::<Vec<_>>();
+        let (pks, values): (Vec<_>, Vec<_>) =
+            columns.into_iter().partition(|(column, _)| column.pk);
+        if pks.is_empty() {
+            return Err(StatementError::MissingPrimaryKey { table }.into());
+        }
+        if values.is_empty() {
+            return Err(StatementError::NothingToUpdate { table }.into());
+        }
+
+        driver.push("UPDATE ");
+        self.table_name.push_to_driver(driver);
+        driver.push(" SET ");
+        for (i, (column, bind)) in values.into_iter().enumerate() {
+            if i > 0 {
+                driver.push(", ");
+            }
+            push_assignment(driver, column.name, bind);
+        }
+        driver.push(" WHERE ");
+        for (i, (column, bind)) in pks.into_iter().enumerate() {
+            if i > 0 {
+                driver.push(" AND ");
+            }
+            push_assignment(driver, column.name, bind);
+        }
+        Ok(())
+    }
+}
+
+/// Sets the columns of the patch on all rows of the table of `R` that match
+/// the filter. The patch is any row, usually one that has a subset of the
+/// columns of `R`.
+pub fn update_where<R, F, P>(filter: F, patch: P) -> UpdateWhere<R, F, P>
+where
+    R: Table,
+{
+    UpdateWhere {
+        filter,
+        patch,
+        row: PhantomData,
+    }
+}
+
+pub struct UpdateWhere<R, F, P> {
+    pub filter: F,
+    pub patch: P,
+    row: PhantomData<fn() -> R>,
+}
+
+impl<DB: Backend, R, F, P> PushSql<DB> for UpdateWhere<R, F, P>
+where
+    R: Row<DB> + Table,
+    F: PushPrql<DB>,
+    P: Row<DB>,
+{
+    fn push_sql(&self, driver: &mut Driver<DB>) -> Result<(), LsorError> {
+        ensure_empty(driver)?;
+
+        let values = written_column_names::<DB, P>()
+            .zip(driver.bind_without_pushing(|driver| self.patch.push_column_values(driver)))
+            .collect::<Vec<_>>();
+        if values.is_empty() {
+            let table = R::table_name().name;
+            return Err(StatementError::NothingToUpdate { table }.into());
+        }
+
+        driver.push("UPDATE ");
+        R::table_name().push_to_driver(driver);
+        driver.push(" SET ");
+        for (i, (column_name, bind)) in values.into_iter().enumerate() {
+            if i > 0 {
+                driver.push(", ");
+            }
+            push_assignment(driver, column_name, bind);
+        }
+        push_where_pk_in::<DB, R, F>(driver, &self.filter)
+    }
+}
+
+/// Deletes all rows of the table of `R` that match the filter.
+pub fn delete_where<R, F>(filter: F) -> DeleteWhere<R, F>
+where
+    R: Table,
+{
+    DeleteWhere {
+        filter,
+        row: PhantomData,
+    }
+}
+
+pub struct DeleteWhere<R, F> {
+    pub filter: F,
+    row: PhantomData<fn() -> R>,
+}
+
+impl<DB: Backend, R, F> PushSql<DB> for DeleteWhere<R, F>
+where
+    R: Row<DB> + Table,
+    F: PushPrql<DB>,
+{
+    fn push_sql(&self, driver: &mut Driver<DB>) -> Result<(), LsorError> {
+        ensure_empty(driver)?;
+
+        driver.push("DELETE FROM ");
+        R::table_name().push_to_driver(driver);
+        push_where_pk_in::<DB, R, F>(driver, &self.filter)
+    }
+}
+
+/// Restricts a statement to the rows whose primary key is selected by the
+/// filtered table of `R`.
+fn push_where_pk_in<DB, R, F>(driver: &mut Driver<DB>, filter: &F) -> Result<(), LsorError>
+where
+    DB: Backend,
+    R: Row<DB> + Table,
+    F: PushPrql<DB>,
+{
+    let pks = <R as Row<DB>>::column_names()
+        .filter(|column| column.pk)
+        .map(|column| column.name)
+        .collect::<Vec<_>>();
+    if pks.is_empty() {
+        let table = R::table_name().name;
+        return Err(StatementError::MissingPrimaryKey { table }.into());
+    }
+
+    driver.push(" WHERE (");
+    push_columns(driver, pks.iter().copied());
+    driver.push(") IN (");
+    driver.push_subquery(&from(R::table_name()).filter(filter).select(pks))?;
+    driver.push(")");
+    Ok(())
+}
+
+fn ensure_empty<DB: Backend>(driver: &Driver<DB>) -> Result<(), StatementError> {
+    // the statement is executed without compilation, so it cannot follow PRQL
+    if driver.is_empty() {
+        Ok(())
+    } else {
+        Err(StatementError::NotEmpty)
+    }
+}
+
+pub(crate) fn push_columns<DB: Backend>(
+    driver: &mut Driver<DB>,
+    columns: impl Iterator<Item = ColumnName>,
+) {
+    for (i, column_name) in columns.enumerate() {
+        if i > 0 {
+            driver.push(", ");
+        }
+        push_ident(driver, column_name.name);
+    }
+}
+
+/// Pushes a quoted identifier, so that Postgres does not fold renamed columns
+/// (e.g. `displayName`) to lowercase.
+pub(crate) fn push_ident<DB: Backend>(driver: &mut Driver<DB>, ident: &str) {
+    driver.push('"');
+    driver.push(ident.replace('"', "\"\""));
+    driver.push('"');
+}
+
+fn push_assignment<DB: Backend>(driver: &mut Driver<DB>, column_name: ColumnName, bind: usize) {
+    push_ident(driver, column_name.name);
+    driver.push(" = $");
+    driver.push(bind);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        column::col,
+        cond::{eq, gt},
+        table::table,
+    };
+
+    struct User {
+        id: i32,
+        email: String,
+        age: i32,
+    }
+
+    impl Table for User {
+        fn table_name() -> TableName {
+            table("users")
+        }
+    }
+
+    impl Row for User {
+        fn column_names() -> impl Iterator<Item = Column> {
+            [
+                Column::new(col("id")).pk(),
+                Column::new(col("email")),
+                Column::new(col("age")),
+            ]
+            .into_iter()
+        }
+
+        fn push_column_values(&self, driver: &mut Driver) {
+            self.id.push_to_driver(driver);
+            driver.push(", ");
+            self.email.push_to_driver(driver);
+            driver.push(", ");
+            self.age.push_to_driver(driver);
+        }
+    }
+
+    struct Age(i32);
+
+    impl Row for Age {
+        fn column_names() -> impl Iterator<Item = Column> {
+            [Column::new(col("age"))].into_iter()
+        }
+
+        fn push_column_values(&self, driver: &mut Driver) {
+            self.0.push_to_driver(driver);
+        }
+    }
+
+    impl Table for Age {
+        fn table_name() -> TableName {
+            table("ages")
+        }
+    }
+
+    struct Id(i32);
+
+    impl Row for Id {
+        fn column_names() -> impl Iterator<Item = Column> {
+            [Column::new(col("id")).pk()].into_iter()
+        }
+
+        fn push_column_values(&self, driver: &mut Driver) {
+            self.0.push_to_driver(driver);
+        }
+    }
+
+    struct Serial(i32);
+
+    impl Row for Serial {
+        fn column_names() -> impl Iterator<Item = Column> {
+            [
+                Column::new(col("id")).pk().default(),
+                Column::new(col("age")),
+            ]
+            .into_iter()
+        }
+
+        fn push_column_values(&self, driver: &mut Driver) {
+            self.0.push_to_driver(driver);
+        }
+    }
+
+    fn user() -> User {
+        User {
+            id: 1,
+            email: "a@lsor.dev".to_string(),
+            age: 42,
+        }
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut driver = Driver::new();
+        insert(user()).push_sql(&mut driver).unwrap();
+        assert_eq!(
+            driver.prql(),
+            "INSERT INTO users (\"id\", \"email\", \"age\") VALUES ($1, $2, $3)"
+        );
+        assert_eq!(driver.binds(), 3);
+    }
+
+    #[test]
+    fn test_update() {
+        let mut driver = Driver::new();
+        update(user()).push_sql(&mut driver).unwrap();
+        assert_eq!(
+            driver.prql(),
+            "UPDATE users SET \"email\" = $2, \"age\" = $3 WHERE \"id\" = $1"
+        );
+        assert_eq!(driver.binds(), 3);
+    }
+
+    #[test]
+    fn test_update_where() {
+        let mut driver = Driver::new();
+        update_where::<User, _, _>(gt(col("age"), 17), Age(18))
+            .push_sql(&mut driver)
+            .unwrap();
+        assert_eq!(
+            driver.prql(),
+            "UPDATE users SET \"age\" = $1 WHERE (\"id\") IN (SELECT id FROM users WHERE age > $2)"
+        );
+        assert_eq!(driver.binds(), 2);
+    }
+
+    #[test]
+    fn test_delete_where() {
+        let mut driver = Driver::new();
+        delete_where::<User, _>(eq(col("email"), "a@lsor.dev"))
+            .push_sql(&mut driver)
+            .unwrap();
+        assert_eq!(
+            driver.prql(),
+            "DELETE FROM users WHERE (\"id\") IN (SELECT id FROM users WHERE email = $1)"
+        );
+        assert_eq!(driver.binds(), 1);
+    }
+
+    #[test]
+    fn test_statement_errors() {
+        let mut driver = Driver::new();
+        driver.push("from users");
+        assert!(matches!(
+            insert(user()).push_sql(&mut driver),
+            Err(LsorError::Statement(StatementError::NotEmpty))
+        ));
+
+        let mut driver = Driver::new();
+        assert!(matches!(
+            Update {
+                table_name: table("ages"),
+                row: Age(18),
+            }
+            .push_sql(&mut driver),
+            Err(LsorError::Statement(StatementError::MissingPrimaryKey {
+                table: "ages"
+            }))
+        ));
+
+        let mut driver = Driver::new();
+        assert!(matches!(
+            Update {
+                table_name: table("users"),
+                row: Serial(18),
+            }
+            .push_sql(&mut driver),
+            Err(LsorError::Statement(StatementError::MissingPrimaryKey {
+                table: "users"
+            }))
+        ));
+
+        let mut driver = Driver::new();
+        assert!(matches!(
+            Update {
+                table_name: table("users"),
+                row: Id(1),
+            }
+            .push_sql(&mut driver),
+            Err(LsorError::Statement(StatementError::NothingToUpdate {
+                table: "users"
+            }))
+        ));
+
+        let mut driver = Driver::new();
+        assert!(matches!(
+            update_where::<Age, _, _>(gt(col("age"), 17), Age(18)).push_sql(&mut driver),
+            Err(LsorError::Statement(StatementError::MissingPrimaryKey {
+                table: "ages"
+            }))
+        ));
+    }
+}
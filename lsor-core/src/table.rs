@@ -1,4 +1,6 @@
 use crate::{
+    backend::Backend,
+    cond::{in_, Between, Eq, Ge, Gt, In, IsNull, Le, Lt, Ne},
     driver::{Driver, PushPrql},
     Order, Sort,
 };
@@ -24,7 +26,7 @@ where
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct TableName {
     pub name: &'static str,
 }
@@ -35,8 +37,8 @@ impl TableName {
     }
 }
 
-impl PushPrql for TableName {
-    fn push_to_driver(&self, driver: &mut Driver) {
+impl<DB: Backend> PushPrql<DB> for TableName {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         driver.push(self.name);
     }
 }
@@ -61,14 +63,50 @@ impl<LHS, RHS> Dot<LHS, RHS> {
             by: self,
         }
     }
+
+    pub fn eq<Value>(&self, rhs: Value) -> Eq<&Self, Value> {
+        Eq { lhs: self, rhs }
+    }
+
+    pub fn ne<Value>(&self, rhs: Value) -> Ne<&Self, Value> {
+        Ne { lhs: self, rhs }
+    }
+
+    pub fn gt<Value>(&self, rhs: Value) -> Gt<&Self, Value> {
+        Gt { lhs: self, rhs }
+    }
+
+    pub fn ge<Value>(&self, rhs: Value) -> Ge<&Self, Value> {
+        Ge { lhs: self, rhs }
+    }
+
+    pub fn lt<Value>(&self, rhs: Value) -> Lt<&Self, Value> {
+        Lt { lhs: self, rhs }
+    }
+
+    pub fn le<Value>(&self, rhs: Value) -> Le<&Self, Value> {
+        Le { lhs: self, rhs }
+    }
+
+    pub fn is_null(&self) -> IsNull<&Self> {
+        IsNull { expr: self }
+    }
+
+    pub fn between<Lo, Hi>(&self, lo: Lo, hi: Hi) -> Between<&Self, Lo, Hi> {
+        Between { expr: self, lo, hi }
+    }
+
+    pub fn in_<Value>(&self, values: impl IntoIterator<Item = Value>) -> In<&Self, Value> {
+        in_(self, values)
+    }
 }
 
-impl<LHS, RHS> PushPrql for Dot<LHS, RHS>
+impl<DB: Backend, LHS, RHS> PushPrql<DB> for Dot<LHS, RHS>
 where
-    LHS: PushPrql,
-    RHS: PushPrql,
+    LHS: PushPrql<DB>,
+    RHS: PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut Driver) {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         self.lhs.push_to_driver(driver);
         driver.push('.');
         self.rhs.push_to_driver(driver);
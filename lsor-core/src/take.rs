@@ -1,6 +1,10 @@
+use std::ops::Range;
+
 use crate::{
+    backend::Backend,
     driver::{Driver, PushPrql},
     filter::Filtered,
+    select::{select_row, Select, SelectRow},
     sort::Sorted,
 };
 
@@ -20,19 +24,51 @@ impl<Query> Taken<Query> {
     pub fn sort<Sort>(&self, sort: Sort) -> Sorted<&Self, Sort> {
         Sorted { query: self, sort }
     }
+
+    pub fn select<Expr>(&self, exprs: impl IntoIterator<Item = Expr>) -> Select<&Self, Expr> {
+        Select {
+            query: self,
+            exprs: exprs.into_iter().collect(),
+        }
+    }
+
+    pub fn select_row<R>(&self) -> SelectRow<&Self, R> {
+        select_row(self)
+    }
 }
 
-impl<Query> PushPrql for Taken<Query>
+impl<DB: Backend, Query> PushPrql<DB> for Taken<Query>
 where
-    Query: PushPrql,
+    Query: PushPrql<DB>,
 {
-    fn push_to_driver(&self, driver: &mut Driver) {
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
         self.query.push_to_driver(driver);
         driver.push("\ntake ");
         driver.push(self.n);
     }
 }
 
+/// Keeps the rows at a range of positions, counting from zero. For example,
+/// `10..20` skips 10 rows and keeps the next 10.
+pub struct TakenRange<Query> {
+    pub query: Query,
+    pub range: Range<usize>,
+}
+
+impl<DB: Backend, Query> PushPrql<DB> for TakenRange<Query>
+where
+    Query: PushPrql<DB>,
+{
+    fn push_to_driver(&self, driver: &mut Driver<DB>) {
+        self.query.push_to_driver(driver);
+        // PRQL ranges count from one and include their end
+        driver.push("\ntake ");
+        driver.push(self.range.start + 1);
+        driver.push("..");
+        driver.push(self.range.end);
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::{column::col, cond::gt, eq, from::from, table::table};
@@ -85,6 +121,21 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_sort_take_range() {
+        let mut driver = Driver::new();
+        {
+            from(table("users"))
+                .sort(col("age").asc())
+                .take_range(20..30)
+                .push_to_driver(&mut driver);
+        }
+        assert_eq!(
+            driver.sql(),
+            "SELECT * FROM users ORDER BY age LIMIT 10 OFFSET 20"
+        );
+    }
+
     #[test]
     fn test_sort_take() {
         let mut driver = Driver::new();
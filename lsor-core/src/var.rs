@@ -1,7 +1,7 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use uuid::Uuid;
 
-use crate::driver::PushPrql;
+use crate::{backend::Backend, driver::PushPrql};
 
 pub fn zero() -> Literal {
     Literal::I32(0)
@@ -17,6 +17,7 @@ pub fn lit(x: impl Into<Literal>) -> Literal {
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
 pub enum Var {
+    Null,
     Bool(bool),
     I32(i32),
     I64(i64),
@@ -25,19 +26,24 @@ pub enum Var {
     String(String),
     Uuid(Uuid),
     DateTime(DateTime<Utc>),
+    NaiveDateTime(NaiveDateTime),
+    Date(NaiveDate),
 }
 
-impl PushPrql for Var {
-    fn push_to_driver(&self, driver: &mut crate::driver::Driver) {
+impl<DB: Backend> PushPrql<DB> for Var {
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
         match self {
-            Self::Bool(x) => driver.push_bind(x),
-            Self::I32(x) => driver.push_bind(x),
-            Self::I64(x) => driver.push_bind(x),
-            Self::F32(x) => driver.push_bind(x),
-            Self::F64(x) => driver.push_bind(x),
-            Self::String(x) => driver.push_bind(x),
-            Self::Uuid(x) => driver.push_bind(x),
-            Self::DateTime(x) => driver.push_bind(x),
+            Self::Null => driver.push("null"),
+            Self::Bool(x) => driver.push_bind(*x),
+            Self::I32(x) => driver.push_bind(*x),
+            Self::I64(x) => driver.push_bind(*x),
+            Self::F32(x) => driver.push_bind(*x),
+            Self::F64(x) => driver.push_bind(*x),
+            Self::String(x) => driver.push_bind(x.clone()),
+            Self::Uuid(x) => driver.push_bind(*x),
+            Self::DateTime(x) => driver.push_bind(*x),
+            Self::NaiveDateTime(x) => driver.push_bind(*x),
+            Self::Date(x) => driver.push_bind(*x),
         };
     }
 }
@@ -81,8 +87,8 @@ impl From<Uuid> for Literal {
     }
 }
 
-impl PushPrql for Literal {
-    fn push_to_driver(&self, driver: &mut crate::driver::Driver) {
+impl<DB: Backend> PushPrql<DB> for Literal {
+    fn push_to_driver(&self, driver: &mut crate::driver::Driver<DB>) {
         match self {
             Self::Bool(x) => driver.push(x),
             Self::I32(x) => driver.push(x),
@@ -1,9 +1,9 @@
 use chrono::{DateTime, Utc};
 use lsor_core::{
-    column::{col, ColumnName},
+    column::col,
     driver::{Driver, PushPrql},
     filter::Filterable,
-    row::{upsert_into, IsPk, Row},
+    row::{upsert_into, Column, Row},
     sort::{Order, Sorting},
     table::table,
 };
@@ -70,10 +70,10 @@ impl Sorting for MetadataSort {
 }
 
 impl Row for Metadata {
-    fn column_names() -> impl Iterator<Item = (ColumnName, IsPk)> {
-        (Some((col(stringify!(name)), false)).into_iter())
-            .chain(Some((col(stringify!(description)), false)))
-            .chain(Some((col(stringify!(tags)), false)))
+    fn column_names() -> impl Iterator<Item = Column> {
+        (Some(Column::new(col(stringify!(name)))).into_iter())
+            .chain(Some(Column::new(col(stringify!(description)))))
+            .chain(Some(Column::new(col(stringify!(tags)))))
     }
 
     fn push_column_values(&self, driver: &mut Driver) {
@@ -143,12 +143,12 @@ pub struct AccountFilter {
 }
 
 impl Row for Account {
-    fn column_names() -> impl Iterator<Item = (ColumnName, IsPk)> {
-        (Some((col(stringify!(id)), true)).into_iter())
-            .chain(Some((col(stringify!(created_at)), false)))
-            .chain(Some((col(stringify!(updated_at)), false)))
-            .chain(Some((col(stringify!(deleted_at)), false)))
-            .chain(Some((col(stringify!(tier)), false)))
+    fn column_names() -> impl Iterator<Item = Column> {
+        (Some(Column::new(col(stringify!(id))).pk()).into_iter())
+            .chain(Some(Column::new(col(stringify!(created_at)))))
+            .chain(Some(Column::new(col(stringify!(updated_at)))))
+            .chain(Some(Column::new(col(stringify!(deleted_at)))))
+            .chain(Some(Column::new(col(stringify!(tier)))))
             .chain(Metadata::column_names())
     }
 
@@ -192,14 +192,14 @@ fn test_column_names() {
     assert_eq!(
         column_names,
         vec![
-            (col("id"), true),
-            (col("created_at"), false),
-            (col("updated_at"), false),
-            (col("deleted_at"), false),
-            (col("tier"), false),
-            (col("name"), false),
-            (col("description"), false),
-            (col("tags"), false),
+            Column::new(col("id")).pk(),
+            Column::new(col("created_at")),
+            Column::new(col("updated_at")),
+            Column::new(col("deleted_at")),
+            Column::new(col("tier")),
+            Column::new(col("name")),
+            Column::new(col("description")),
+            Column::new(col("tags")),
         ]
     );
 }
@@ -239,5 +239,5 @@ fn test_upsert() {
     };
     let mut driver = Driver::new();
     upsert_into(table("accounts"), &account).push_to_driver(&mut driver);
-    assert_eq!(driver.prql(), "INSERT INTO accounts (id, created_at, updated_at, deleted_at, tier, name, description, tags) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) ON CONFLICT (id) DO UPDATE SET (created_at, updated_at, deleted_at, tier, name, description, tags) = ($2, $3, $4, $5, $6, $7, $8)");
+    assert_eq!(driver.prql(), "INSERT INTO accounts (\"id\", \"created_at\", \"updated_at\", \"deleted_at\", \"tier\", \"name\", \"description\", \"tags\") VALUES ($1, $2, $3, $4, $5, $6, $7, $8) ON CONFLICT (\"id\") DO UPDATE SET \"created_at\" = EXCLUDED.\"created_at\", \"updated_at\" = EXCLUDED.\"updated_at\", \"deleted_at\" = EXCLUDED.\"deleted_at\", \"tier\" = EXCLUDED.\"tier\", \"name\" = EXCLUDED.\"name\", \"description\" = EXCLUDED.\"description\", \"tags\" = EXCLUDED.\"tags\"");
 }
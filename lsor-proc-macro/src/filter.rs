@@ -1,7 +1,7 @@
 use proc_macro::TokenStream;
 use proc_macro2::Span;
 use quote::quote;
-use syn::{Attribute, Data, DataEnum, DataStruct, DeriveInput, Fields, Ident};
+use syn::{Attribute, Data, DataEnum, DataStruct, DeriveInput, Fields, FieldsNamed, Ident};
 
 use crate::util;
 
@@ -61,19 +61,20 @@ fn expand_derive_filter_for_struct(
             Span::call_site(),
         );
 
+        let column_name = util::column_name(field, attrs);
         let flat = util::has_flatten_attr(&field.attrs);
         let json = util::has_json_attr(&field.attrs);
         if flat {
             Some(quote! { #filter_ident::#field_ident_camel_case(filter) => {
-                    filter.push_to_driver_with_table_name(tn, driver);
+                    ::lsor::filter::Filtering::<__DB>::push_filter_to_driver_with_table_name(filter, tn, driver);
             }})
         } else if json {
             Some(quote! { #filter_ident::#field_ident_camel_case(filter) => {
-                filter.push_to_driver_as_json(&::lsor::table::dot(tn, ::lsor::column::col(stringify!(#field_ident))), driver);
+                ::lsor::filter::Filtering::<__DB>::push_json_filter_to_driver(filter, &::lsor::table::dot(tn, ::lsor::column::col(#column_name)), driver);
             }})
         } else {
             Some(quote! { #filter_ident::#field_ident_camel_case(filter) => {
-                filter.push_to_driver(&::lsor::table::dot(tn, ::lsor::column::col(stringify!(#field_ident))), driver);
+                ::lsor::filter::Filtering::<__DB>::push_filter_to_driver(filter, &::lsor::table::dot(tn, ::lsor::column::col(#column_name)), driver);
             }})
         }
     });
@@ -110,7 +111,7 @@ fn expand_derive_filter_for_struct(
             //
 
             Some(quote! { #filter_ident::#field_ident_camel_case(filter) => {
-                filter.push_to_driver(lhs, driver);
+                ::lsor::filter::Filtering::<__DB>::push_filter_to_driver(filter, lhs, driver);
             }})
         })
         .collect::<Vec<_>>();
@@ -132,32 +133,42 @@ fn expand_derive_filter_for_struct(
 
             let flat = util::has_flatten_attr(&field.attrs);
             if flat {
-                Some(quote! { #filter_ident::#field_ident_camel_case(filter) => filter.push_to_driver_as_json(lhs, driver), })
+                Some(quote! { #filter_ident::#field_ident_camel_case(filter) => ::lsor::filter::Filtering::<__DB>::push_json_filter_to_driver(filter, lhs, driver), })
             } else {
                 Some(quote! { #filter_ident::#field_ident_camel_case(filter) => {
-                    filter.push_to_driver_as_json(lhs, driver);
+                    ::lsor::filter::Filtering::<__DB>::push_json_filter_to_driver(filter, lhs, driver);
                 }})
             }
         })
         .collect::<Vec<_>>();
 
+    // the filter can be pushed to any backend that can push the filters of all
+    // of its fields
+    let filtering_predicates = field_filtering_predicates(fields);
+    let inherent_impls = expand_inherent_filter_impls();
+
     let push_to_drive_impl = table.map(|table| {
         quote! {
-            impl ::lsor::driver::PushPrql for #filter_ident {
-                fn push_to_driver(&self, driver: &mut ::lsor::driver::Driver) {
-                    self.push_to_driver_with_table_name(&::lsor::table::table(#table), driver);
+            impl<__DB: ::lsor::backend::Backend> ::lsor::driver::PushPrql<__DB> for #filter_ident
+            where
+                Self: ::lsor::filter::Filtering<__DB>,
+            {
+                fn push_to_driver(&self, driver: &mut ::lsor::driver::Driver<__DB>) {
+                    ::lsor::filter::Filtering::<__DB>::push_filter_to_driver_with_table_name(self, &::lsor::table::table(#table), driver);
                 }
             }
         }
     });
 
+    let graphql = util::expand_graphql_oneof_object();
+
     let expanded = quote! {
         impl ::lsor::filter::Filterable for #ident {
             type Filter = #filter_ident;
         }
 
-        #[derive(::std::clone::Clone, ::std::fmt::Debug, ::async_graphql::OneofObject)]
-        #[graphql(rename_fields = "snake_case")]
+        #[derive(::std::clone::Clone, ::std::fmt::Debug)]
+        #graphql
         pub enum #filter_ident {
             All(Vec<#filter_ident>),
             Any(Vec<#filter_ident>),
@@ -166,14 +177,17 @@ fn expand_derive_filter_for_struct(
 
         #push_to_drive_impl
 
-        impl #filter_ident {
-            pub fn push_to_driver_with_table_name(&self, tn: &dyn ::lsor::driver::PushPrql, driver: &mut ::lsor::driver::Driver) {
+        impl<__DB: ::lsor::backend::Backend> ::lsor::filter::Filtering<__DB> for #filter_ident
+        where
+            #(#filtering_predicates)*
+        {
+            fn push_filter_to_driver_with_table_name(&self, tn: &dyn ::lsor::driver::PushPrql<__DB>, driver: &mut ::lsor::driver::Driver<__DB>) {
                 match &self {
                     #filter_ident::All(all) => {
                         let n = all.len();
                         for (i, x) in all.iter().enumerate() {
                             driver.push('(');
-                            x.push_to_driver_with_table_name(tn, driver);
+                            ::lsor::filter::Filtering::<__DB>::push_filter_to_driver_with_table_name(x, tn, driver);
                             if i < n - 1 {
                                 driver.push(") && ");
                             } else {
@@ -185,7 +199,7 @@ fn expand_derive_filter_for_struct(
                         let n = any.len();
                         for (i, x) in any.iter().enumerate() {
                             driver.push('(');
-                            x.push_to_driver_with_table_name(tn, driver);
+                            ::lsor::filter::Filtering::<__DB>::push_filter_to_driver_with_table_name(x, tn, driver);
                             if i < n - 1 {
                                 driver.push(") || ");
                             } else {
@@ -197,13 +211,13 @@ fn expand_derive_filter_for_struct(
                 }
             }
 
-            pub fn push_to_driver(&self, lhs: &dyn ::lsor::driver::PushPrql, driver: &mut ::lsor::driver::Driver) {
+            fn push_filter_to_driver(&self, lhs: &dyn ::lsor::driver::PushPrql<__DB>, driver: &mut ::lsor::driver::Driver<__DB>) {
                 match self {
                     #filter_ident::All(all) => {
                         let n = all.len();
                         for (i, x) in all.iter().enumerate() {
                             driver.push('(');
-                            x.push_to_driver(lhs, driver);
+                            ::lsor::filter::Filtering::<__DB>::push_filter_to_driver(x, lhs, driver);
                             if i < n - 1 {
                                 driver.push(") && ");
                             } else {
@@ -215,7 +229,7 @@ fn expand_derive_filter_for_struct(
                         let n = any.len();
                         for (i, x) in any.iter().enumerate() {
                             driver.push('(');
-                            x.push_to_driver(lhs, driver);
+                            ::lsor::filter::Filtering::<__DB>::push_filter_to_driver(x, lhs, driver);
                             if i < n - 1 {
                                 driver.push(") || ");
                             } else {
@@ -227,13 +241,13 @@ fn expand_derive_filter_for_struct(
                 }
             }
 
-            pub fn push_to_driver_as_json(&self, lhs: &dyn ::lsor::driver::PushPrql, driver: &mut ::lsor::driver::Driver) {
+            fn push_json_filter_to_driver(&self, lhs: &dyn ::lsor::driver::PushPrql<__DB>, driver: &mut ::lsor::driver::Driver<__DB>) {
                 match self {
                     #filter_ident::All(all) => {
                         let n = all.len();
                         for (i, x) in all.iter().enumerate() {
                             driver.push('(');
-                            x.push_to_driver_as_json(lhs, driver);
+                            ::lsor::filter::Filtering::<__DB>::push_json_filter_to_driver(x, lhs, driver);
                             if i < n - 1 {
                                 driver.push(") && ");
                             } else {
@@ -245,7 +259,7 @@ fn expand_derive_filter_for_struct(
                         let n = any.len();
                         for (i, x) in any.iter().enumerate() {
                             driver.push('(');
-                            x.push_to_driver_as_json(lhs, driver);
+                            ::lsor::filter::Filtering::<__DB>::push_json_filter_to_driver(x, lhs, driver);
                             if i < n - 1 {
                                 driver.push(") || ");
                             } else {
@@ -257,6 +271,18 @@ fn expand_derive_filter_for_struct(
                 }
             }
         }
+
+        impl #filter_ident {
+            pub fn push_to_driver_with_table_name<__DB>(&self, tn: &dyn ::lsor::driver::PushPrql<__DB>, driver: &mut ::lsor::driver::Driver<__DB>)
+            where
+                __DB: ::lsor::backend::Backend,
+                Self: ::lsor::filter::Filtering<__DB>,
+            {
+                ::lsor::filter::Filtering::<__DB>::push_filter_to_driver_with_table_name(self, tn, driver)
+            }
+
+            #inherent_impls
+        }
     };
 
     TokenStream::from(expanded)
@@ -304,12 +330,13 @@ fn expand_derive_json_filter_for_struct(
             Span::call_site(),
         );
 
+        let column_name = util::column_name(field, attrs);
         let flat = util::has_flatten_attr(&field.attrs);
         if flat {
             panic!("cannot use the #[lsor(flatten)] attribute with the #[lsor(json)] attribute")
         } else {
             Some(quote! { #filter_ident::#field_ident_camel_case(filter) => {
-                filter.push_to_driver_as_json(&::lsor::column::json(lhs).get(stringify!(#field_ident)), driver);
+                ::lsor::filter::Filtering::<__DB>::push_json_filter_to_driver(filter, &::lsor::column::json(lhs).get(#column_name), driver);
             }})
         }
     });
@@ -326,54 +353,72 @@ fn expand_derive_json_filter_for_struct(
             Span::call_site(),
         );
 
+        let column_name = util::column_name(field, attrs);
         let flat = util::has_flatten_attr(&field.attrs);
         if flat {
             panic!("cannot use the #[lsor(flatten)] attribute with the #[lsor(json)] attribute")
         } else {
             Some(quote! { #filter_ident::#field_ident_camel_case(filter) => {
-                filter.push_to_driver_as_json(&::lsor::column::json(lhs).get(stringify!(#field_ident)), driver);
+                ::lsor::filter::Filtering::<__DB>::push_json_filter_to_driver(filter, &::lsor::column::json(lhs).get(#column_name), driver);
             }})
         }
     });
 
     let _push_to_drive_impl = table.map(|table| {
         quote! {
-            impl ::lsor::driver::PushPrql for #filter_ident {
-                fn push_to_driver(&self, driver: &mut ::lsor::driver::Driver) {
-                    self.push_to_driver_with_table_name(&::lsor::table::table(#table), driver);
+            impl<__DB: ::lsor::backend::Backend> ::lsor::driver::PushPrql<__DB> for #filter_ident
+            where
+                Self: ::lsor::filter::Filtering<__DB>,
+            {
+                fn push_to_driver(&self, driver: &mut ::lsor::driver::Driver<__DB>) {
+                    ::lsor::filter::Filtering::<__DB>::push_filter_to_driver_with_table_name(self, &::lsor::table::table(#table), driver);
                 }
             }
         }
     });
 
+    // the filter can be pushed to any backend that can push the filters of all
+    // of its fields
+    let filtering_predicates = field_filtering_predicates(fields);
+    let inherent_impls = expand_inherent_filter_impls();
+
     let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
 
+    let graphql = util::expand_graphql_oneof_object();
+
     let expanded = quote! {
         impl #impl_generics ::lsor::filter::Filterable for #ident #ty_generics #where_clause {
             type Filter = #filter_ident;
         }
 
-        #[derive(::std::clone::Clone, ::std::fmt::Debug, ::async_graphql::OneofObject)]
-        #[graphql(rename_fields = "snake_case")]
+        #[derive(::std::clone::Clone, ::std::fmt::Debug)]
+        #graphql
         pub enum #filter_ident {
             #(#field_variants_decl)*
         }
 
         // #push_to_drive_impl
 
-        impl #filter_ident {
-            pub fn push_to_driver(&self, lhs: &dyn ::lsor::driver::PushPrql, driver: &mut ::lsor::driver::Driver) {
+        impl<__DB: ::lsor::backend::Backend> ::lsor::filter::Filtering<__DB> for #filter_ident
+        where
+            #(#filtering_predicates)*
+        {
+            fn push_filter_to_driver(&self, lhs: &dyn ::lsor::driver::PushPrql<__DB>, driver: &mut ::lsor::driver::Driver<__DB>) {
                 match &self {
                     #(#field_variants_impl)*
                 }
             }
 
-            pub fn push_to_driver_as_json(&self, lhs: &dyn ::lsor::driver::PushPrql, driver: &mut ::lsor::driver::Driver) {
+            fn push_json_filter_to_driver(&self, lhs: &dyn ::lsor::driver::PushPrql<__DB>, driver: &mut ::lsor::driver::Driver<__DB>) {
                 match &self {
                     #(#field_variants_as_json_impl)*
                 }
             }
         }
+
+        impl #filter_ident {
+            #inherent_impls
+        }
     };
 
     TokenStream::from(expanded)
@@ -416,42 +461,42 @@ fn expand_derive_filter_for_enum(
                 #filter_ident::Eq(x) => {
                     lhs.push_to_driver(driver);
                     driver.push(" == ");
-                    driver.push_bind(x);
+                    driver.push_bind(x.clone());
                 }
             },
             "!=" => quote! {
                 #filter_ident::Ne(x) => {
                     lhs.push_to_driver(driver);
                     driver.push(" != ");
-                    driver.push_bind(x);
+                    driver.push_bind(x.clone());
                 }
             },
             "<" => quote! {
                 #filter_ident::Lt(x) => {
                     lhs.push_to_driver(driver);
                     driver.push(" < ");
-                    driver.push_bind(x);
+                    driver.push_bind(x.clone());
                 }
             },
             "<=" => quote! {
                 #filter_ident::Le(x) => {
                     lhs.push_to_driver(driver);
                     driver.push(" <= ");
-                    driver.push_bind(x);
+                    driver.push_bind(x.clone());
                 }
             },
             ">" => quote! {
                 #filter_ident::Gt(x) => {
                     lhs.push_to_driver(driver);
                     driver.push(" > ");
-                    driver.push_bind(x);
+                    driver.push_bind(x.clone());
                 }
             },
             ">=" => quote! {
                 #filter_ident::Ge(x) => {
                     lhs.push_to_driver(driver);
                     driver.push(" >= ");
-                    driver.push_bind(x);
+                    driver.push_bind(x.clone());
                 }
             },
             _ => panic!(
@@ -468,42 +513,42 @@ fn expand_derive_filter_for_enum(
                 #filter_ident::Eq(x) => {
                     lhs.push_to_driver(driver);
                     driver.push(" == ");
-                    driver.push_bind(::sqlx::types::Json(x));
+                    driver.push_bind_json(x);
                 }
             },
             "!=" => quote! {
                 #filter_ident::Ne(x) => {
                     lhs.push_to_driver(driver);
                     driver.push(" != ");
-                    driver.push_bind(::sqlx::types::Json(x));
+                    driver.push_bind_json(x);
                 }
             },
             "<" => quote! {
                 #filter_ident::Lt(x) => {
                     lhs.push_to_driver(driver);
                     driver.push(" < ");
-                    driver.push_bind(::sqlx::types::Json(x));
+                    driver.push_bind_json(x);
                 }
             },
             "<=" => quote! {
                 #filter_ident::Le(x) => {
                     lhs.push_to_driver(driver);
                     driver.push(" <= ");
-                    driver.push_bind(::sqlx::types::Json(x));
+                    driver.push_bind_json(x);
                 }
             },
             ">" => quote! {
                 #filter_ident::Gt(x) => {
                     lhs.push_to_driver(driver);
                     driver.push(" > ");
-                    driver.push_bind(::sqlx::types::Json(x));
+                    driver.push_bind_json(x);
                 }
             },
             ">=" => quote! {
                 #filter_ident::Ge(x) => {
                     lhs.push_to_driver(driver);
                     driver.push(" >= ");
-                    driver.push_bind(::sqlx::types::Json(x));
+                    driver.push_bind_json(x);
                 }
             },
             _ => panic!(
@@ -513,35 +558,82 @@ fn expand_derive_filter_for_enum(
         })
         .collect::<Vec<_>>();
 
+    let inherent_impls = expand_inherent_filter_impls();
+
+    let graphql = util::expand_graphql_oneof_object();
+
     let expanded = quote! {
         impl ::lsor::filter::Filterable for #ident {
             type Filter = #filter_ident;
         }
 
-        #[derive(::std::clone::Clone, ::std::fmt::Debug, ::async_graphql::OneofObject)]
-        #[graphql(rename_fields = "snake_case")]
+        #[derive(::std::clone::Clone, ::std::fmt::Debug)]
+        #graphql
         pub enum #filter_ident {
             #(#variants,)*
         }
 
-        impl #filter_ident {
-            pub fn push_to_driver(&self, lhs: &dyn ::lsor::driver::PushPrql, driver: &mut ::lsor::driver::Driver) {
+        impl<__DB> ::lsor::filter::Filtering<__DB> for #filter_ident
+        where
+            __DB: ::lsor::backend::Backend + ::lsor::backend::Encodes<#ident>,
+        {
+            fn push_filter_to_driver(&self, lhs: &dyn ::lsor::driver::PushPrql<__DB>, driver: &mut ::lsor::driver::Driver<__DB>) {
                 match self {
                     #(#match_arms)*
                 }
             }
 
-            pub fn push_to_driver_as_json(&self, lhs: &dyn ::lsor::driver::PushPrql, driver: &mut ::lsor::driver::Driver) {
+            fn push_json_filter_to_driver(&self, lhs: &dyn ::lsor::driver::PushPrql<__DB>, driver: &mut ::lsor::driver::Driver<__DB>) {
                 match self {
                     #(#match_arms_as_json)*
                 }
             }
         }
+
+        impl #filter_ident {
+            #inherent_impls
+        }
     };
 
     TokenStream::from(expanded)
 }
 
+/// Expands the where-clause predicates that require the filter of every field
+/// to be pushable to the backend `__DB`.
+fn field_filtering_predicates(fields: &FieldsNamed) -> Vec<proc_macro2::TokenStream> {
+    fields
+        .named
+        .iter()
+        .filter(|field| !util::has_skip_filter_attr(&field.attrs))
+        .map(|field| {
+            let field_ty = &field.ty;
+            quote! { <#field_ty as ::lsor::filter::Filterable>::Filter: ::lsor::filter::Filtering<__DB>, }
+        })
+        .collect()
+}
+
+/// Expands the inherent methods that push a derived filter to the driver. They
+/// only delegate to the implementation of `Filtering`.
+fn expand_inherent_filter_impls() -> proc_macro2::TokenStream {
+    quote! {
+        pub fn push_to_driver<__DB>(&self, lhs: &dyn ::lsor::driver::PushPrql<__DB>, driver: &mut ::lsor::driver::Driver<__DB>)
+        where
+            __DB: ::lsor::backend::Backend,
+            Self: ::lsor::filter::Filtering<__DB>,
+        {
+            ::lsor::filter::Filtering::<__DB>::push_filter_to_driver(self, lhs, driver)
+        }
+
+        pub fn push_to_driver_as_json<__DB>(&self, lhs: &dyn ::lsor::driver::PushPrql<__DB>, driver: &mut ::lsor::driver::Driver<__DB>)
+        where
+            __DB: ::lsor::backend::Backend,
+            Self: ::lsor::filter::Filtering<__DB>,
+        {
+            ::lsor::filter::Filtering::<__DB>::push_json_filter_to_driver(self, lhs, driver)
+        }
+    }
+}
+
 const fn filter_attrs_str() -> &'static str {
     "'==', '!=', '<', '<=', '>', or '>='"
 }
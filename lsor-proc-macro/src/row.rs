@@ -2,7 +2,8 @@ use proc_macro::TokenStream;
 use proc_macro2::Span;
 use quote::quote;
 use syn::{
-    parse_quote, Data, DeriveInput, Fields, GenericParam, Lifetime, LifetimeDef, WherePredicate,
+    parse_quote, Data, DeriveInput, Field, Fields, GenericParam, Lifetime, LifetimeDef,
+    WherePredicate,
 };
 
 use crate::util;
@@ -24,7 +25,16 @@ pub fn expand_derive_row(input: TokenStream) -> TokenStream {
         _ => panic!("row can only be implemented for structs with named fields"),
     };
 
-    // expand the implementation of FromRow<'r, PgRow>
+    // updates and upserts find the row by its primary key, so it must be written
+    for field in fields {
+        if util::has_pk_attr(&field.attrs)
+            && (util::has_readonly_attr(&field.attrs) || util::has_default_attr(&field.attrs))
+        {
+            panic!("primary key can not be readonly or default");
+        }
+    }
+
+    // expand the implementation of FromRow<'r, R>
     let from_row_impl = fields.iter().map(|field| {
         let field_ident = field.ident.as_ref().unwrap();
 
@@ -34,48 +44,93 @@ pub fn expand_derive_row(input: TokenStream) -> TokenStream {
         }
 
         let json = util::has_json_attr(&field.attrs);
+        let column_name = util::column_name(field, &ast.attrs);
 
         let flat = util::has_flatten_attr(&field.attrs);
         if flat {
             quote! { #field_ident: <_>::from_row(row)?, }
         } else if json {
-            quote! { #field_ident: row.try_get::<::sqlx::types::Json<_>, _>(stringify!(#field_ident))?.0, }
+            quote! { #field_ident: row.try_get::<::sqlx::types::Json<_>, _>(#column_name)?.0, }
             // quote! { #field_ident: row.try_get(stringify!(#field_ident))?, }
         } else {
-            quote! { #field_ident: row.try_get(stringify!(#field_ident))?, }
+            quote! { #field_ident: row.try_get(#column_name)?, }
         }
     });
 
-    let num_filtered_fields = fields
-        .iter()
-        .filter(|field| !util::has_skip_attr(&field.attrs))
-        .count();
+    // expand the implementation of FromPrefixedRow<'r, R>, which reads the
+    // same fields from the columns with a prefix
+    let from_prefixed_row_impl = fields.iter().map(|field| {
+        let field_ident = field.ident.as_ref().unwrap();
+
+        let skip = util::has_skip_attr(&field.attrs);
+        if skip {
+            return quote! { #field_ident: ::std::default::Default::default(), };
+        }
+
+        let json = util::has_json_attr(&field.attrs);
+        let column_name = util::column_name(field, &ast.attrs);
 
-    // expand the implementation of Row::column_names
-    let column_names_impl = fields.iter().filter(|field| !util::has_skip_attr(&field.attrs)).enumerate().map(|(i, field)| {
         let flat = util::has_flatten_attr(&field.attrs);
-        let postfix = if i < num_filtered_fields - 1 {
-            quote! { .chain }
-        } else {
-            quote! { }
-        };
         if flat {
-            let field_type = &field.ty;
-            quote! { (<#field_type as ::lsor::row::Row>::column_names()) #postfix }
+            quote! { #field_ident: ::lsor::row::FromPrefixedRow::from_prefixed_row(row, prefix)?, }
+        } else if json {
+            quote! { #field_ident: row.try_get::<::sqlx::types::Json<_>, _>(::std::format!("{}{}", prefix, #column_name).as_str())?.0, }
         } else {
-            let field_ident = field.ident.as_ref().unwrap();
-            let field_pk = util::has_pk_attr(&field.attrs);
-            quote! { (Some((::lsor::column::col(stringify!(#field_ident)), #field_pk)).into_iter()) #postfix }
+            quote! { #field_ident: row.try_get(::std::format!("{}{}", prefix, #column_name).as_str())?, }
         }
     });
 
-    // expand the implementation of Row::column_values
-    let push_column_values_impl = fields
+    let num_filtered_fields = fields
+        .iter()
+        .filter(|field| !util::has_skip_attr(&field.attrs))
+        .count();
+
+    // expand the implementation of Row::column_names
+    let column_names_impl = fields
         .iter()
         .filter(|field| !util::has_skip_attr(&field.attrs))
         .enumerate()
         .map(|(i, field)| {
+            let flat = util::has_flatten_attr(&field.attrs);
             let postfix = if i < num_filtered_fields - 1 {
+                quote! { .chain }
+            } else {
+                quote! {}
+            };
+            if flat {
+                let field_type = &field.ty;
+                quote! { (<#field_type as ::lsor::row::Row<__DB>>::column_names()) #postfix }
+            } else {
+                let column_name = util::column_name(field, &ast.attrs);
+                let field_pk = util::has_pk_attr(&field.attrs);
+                let field_readonly = util::has_readonly_attr(&field.attrs);
+                let field_default = util::has_default_attr(&field.attrs);
+                quote! {
+                    (Some(::lsor::row::Column {
+                        name: ::lsor::column::col(#column_name),
+                        pk: #field_pk,
+                        readonly: #field_readonly,
+                        default: #field_default,
+                    }).into_iter()) #postfix
+                }
+            }
+        });
+
+    // readonly and defaulted columns are read, but never written
+    let is_written = |field: &&Field| {
+        !util::has_skip_attr(&field.attrs)
+            && !util::has_readonly_attr(&field.attrs)
+            && !util::has_default_attr(&field.attrs)
+    };
+    let num_written_fields = fields.iter().filter(is_written).count();
+
+    // expand the implementation of Row::column_values
+    let push_column_values_impl = fields
+        .iter()
+        .filter(is_written)
+        .enumerate()
+        .map(|(i, field)| {
+            let postfix = if i < num_written_fields - 1 {
                 quote! { driver.push(", "); }
             } else {
                 quote! {}
@@ -86,7 +141,7 @@ pub fn expand_derive_row(input: TokenStream) -> TokenStream {
             let json = util::has_json_attr(&field.attrs);
 
             if json {
-                quote! { driver.push_bind(::sqlx::types::Json(&self.#field_ident)); #postfix }
+                quote! { driver.push_bind_json(&self.#field_ident); #postfix }
             } else {
                 let flat = util::has_flatten_attr(&field.attrs);
                 if flat {
@@ -97,6 +152,37 @@ pub fn expand_derive_row(input: TokenStream) -> TokenStream {
             }
         });
 
+    // the row can be pushed to any backend that can bind all of its fields
+    let row_predicates = fields
+        .iter()
+        .filter(is_written)
+        .filter(|field| !util::has_json_attr(&field.attrs))
+        .map(|field| {
+            let field_ty = &field.ty;
+            if util::has_flatten_attr(&field.attrs) {
+                quote! { #field_ty: ::lsor::row::Row<__DB> }
+            } else {
+                quote! { #field_ty: ::lsor::driver::PushPrql<__DB> }
+            }
+        });
+
+    // the row can be read from any backend that can decode all of its fields
+    let from_row_predicates = |flat_trait| {
+        fields
+        .iter()
+        .filter(|field| !util::has_skip_attr(&field.attrs))
+        .map(move |field| {
+            let field_ty = &field.ty;
+            if util::has_flatten_attr(&field.attrs) {
+                quote! { #field_ty: #flat_trait }
+            } else if util::has_json_attr(&field.attrs) {
+                quote! { ::sqlx::types::Json<#field_ty>: ::sqlx::Decode<'__sqlx__FromRow, __R::Database> + ::sqlx::Type<__R::Database> }
+            } else {
+                quote! { #field_ty: ::sqlx::Decode<'__sqlx__FromRow, __R::Database> + ::sqlx::Type<__R::Database> }
+            }
+        })
+    };
+
     // capture the generics before we modify them with the new liftime
     let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
 
@@ -110,40 +196,96 @@ pub fn expand_derive_row(input: TokenStream) -> TokenStream {
         }
     });
 
+    // expand the implementation of Row::conflict_target, if the row does not
+    // conflict on its primary key
+    let conflict_target_impl = match (
+        util::collect_unique_attr(&ast.attrs),
+        util::collect_conflict_attr(&ast.attrs),
+    ) {
+        (Some(_), Some(_)) => panic!("row can only have one of unique and conflict"),
+        (Some(columns), None) => Some(quote! {
+            fn conflict_target() -> ::lsor::row::ConflictTarget {
+                ::lsor::row::ConflictTarget::Columns(::std::vec![#(::lsor::column::col(#columns)),*])
+            }
+        }),
+        (None, Some(constraint)) => Some(quote! {
+            fn conflict_target() -> ::lsor::row::ConflictTarget {
+                ::lsor::row::ConflictTarget::Constraint(#constraint)
+            }
+        }),
+        (None, None) => None,
+    };
+
+    // introduce the backend that the row is pushed to
+    let mut generics = ast.generics.clone();
+    generics
+        .params
+        .push(parse_quote!(__DB: ::lsor::backend::Backend));
+    generics
+        .make_where_clause()
+        .predicates
+        .extend(row_predicates.map(|predicate| -> WherePredicate { parse_quote!(#predicate) }));
+    let (impl_generics_with_db, _ty_generics, where_clause_with_db) = generics.split_for_impl();
+
     let impl_row_trait = quote! {
-        impl #impl_generics ::lsor::row::Row for #ident #ty_generics #where_clause {
-            fn column_names() -> impl ::std::iter::Iterator<Item = (::lsor::column::ColumnName, bool)> {
+        impl #impl_generics_with_db ::lsor::row::Row<__DB> for #ident #ty_generics #where_clause_with_db {
+            fn column_names() -> impl ::std::iter::Iterator<Item = ::lsor::row::Column> {
                 use ::lsor::row::Row;
 
                 #(#column_names_impl)*
             }
 
-            fn push_column_values(&self, driver: &mut ::lsor::driver::Driver) {
+            fn push_column_values(&self, driver: &mut ::lsor::driver::Driver<__DB>) {
                 use ::lsor::driver::PushPrql;
                 use ::lsor::row::Row;
 
                 #(#push_column_values_impl)*
             }
+
+            #conflict_target_impl
         }
     };
 
-    // introduce the new lifteime that is needed for the FromRow trait
+    // introduce the new lifteime and the row type that are needed for the
+    // FromRow trait
     let mut generics = ast.generics.clone();
     let lifetime = Lifetime::new("'__sqlx__FromRow", Span::call_site());
     generics
         .params
         .insert(0, GenericParam::Lifetime(LifetimeDef::new(lifetime)));
+    generics.params.push(parse_quote!(__R: ::sqlx::Row));
+    let mut prefixed_generics = generics.clone();
+    let where_clause = generics.make_where_clause();
+    where_clause
+        .predicates
+        .push(parse_quote!(&'__sqlx__FromRow str: ::sqlx::ColumnIndex<__R>));
+    where_clause.predicates.extend(
+        from_row_predicates(quote! { ::sqlx::FromRow<'__sqlx__FromRow, __R> })
+            .map(|predicate| -> WherePredicate { parse_quote!(#predicate) }),
+    );
+
+    // the prefixed column names are formatted, so they can have any lifetime
+    let where_clause = prefixed_generics.make_where_clause();
+    where_clause
+        .predicates
+        .push(parse_quote!(for<'__a> &'__a str: ::sqlx::ColumnIndex<__R>));
+    where_clause.predicates.extend(
+        from_row_predicates(quote! { ::lsor::row::FromPrefixedRow<'__sqlx__FromRow, __R> })
+            .map(|predicate| -> WherePredicate { parse_quote!(#predicate) }),
+    );
 
     // re-capture the impl_generics
-    let (impl_generics, _ty_generics, _where_clause) = generics.split_for_impl();
+    let (impl_generics, _ty_generics, where_clause) = generics.split_for_impl();
+    let (prefixed_impl_generics, _ty_generics, prefixed_where_clause) =
+        prefixed_generics.split_for_impl();
 
     let expanded = quote! {
         #impl_table_trait
 
         #impl_row_trait
 
-        impl #impl_generics ::sqlx::FromRow<'__sqlx__FromRow, ::sqlx::postgres::PgRow> for #ident #ty_generics #where_clause {
-            fn from_row(row: &'__sqlx__FromRow ::sqlx::postgres::PgRow) -> ::sqlx::Result<Self> {
+        impl #impl_generics ::sqlx::FromRow<'__sqlx__FromRow, __R> for #ident #ty_generics #where_clause {
+            fn from_row(row: &'__sqlx__FromRow __R) -> ::sqlx::Result<Self> {
                 use ::sqlx::Row;
 
                 Ok(Self {
@@ -151,6 +293,16 @@ pub fn expand_derive_row(input: TokenStream) -> TokenStream {
                 })
             }
         }
+
+        impl #prefixed_impl_generics ::lsor::row::FromPrefixedRow<'__sqlx__FromRow, __R> for #ident #ty_generics #prefixed_where_clause {
+            fn from_prefixed_row(row: &'__sqlx__FromRow __R, prefix: &str) -> ::sqlx::Result<Self> {
+                use ::sqlx::Row;
+
+                Ok(Self {
+                    #(#from_prefixed_row_impl)*
+                })
+            }
+        }
     };
 
     TokenStream::from(expanded)
@@ -195,24 +347,32 @@ pub fn expand_derive_json_row(mut ast: DeriveInput) -> TokenStream {
     // re-capture the impl_generics
     let (impl_generics_with_sqlx_lifetime, _ty_generics, _where_clause) = generics.split_for_impl();
 
+    // introduce the backend that the row is pushed to
+    let mut generics = ast.generics.clone();
+    generics
+        .params
+        .push(parse_quote!(__DB: ::lsor::backend::Backend));
+    let (impl_generics_with_db, _ty_generics, where_clause_with_db) = generics.split_for_impl();
+
     let expanded = quote! {
-        impl #impl_generics ::lsor::driver::PushPrql for #ident #ty_generics #where_clause {
-            fn push_to_driver(&self, driver: &mut ::lsor::driver::Driver) {
-                driver.push_bind(self);
+        impl #impl_generics_with_db ::lsor::driver::PushPrql<__DB> for #ident #ty_generics #where_clause_with_db {
+            fn push_to_driver(&self, driver: &mut ::lsor::driver::Driver<__DB>) {
+                driver.push_bind_json(self);
             }
         }
 
         impl #impl_generics ::sqlx::Type<::sqlx::Postgres> for #ident #ty_generics #where_clause {
             fn type_info() -> <::sqlx::Postgres as ::sqlx::Database>::TypeInfo {
-                ::sqlx::types::JsonValue::type_info()
+                <::sqlx::types::JsonValue as ::sqlx::Type<::sqlx::Postgres>>::type_info()
             }
         }
 
         impl #impl_generics_with_sqlx_lifetime ::sqlx::Encode<'__sqlx__EncodeDecode, ::sqlx::Postgres> for #ident #ty_generics #where_clause {
             fn encode_by_ref(&self, buf: &mut <::sqlx::Postgres as ::sqlx::database::HasArguments<'__sqlx__EncodeDecode>>::ArgumentBuffer) -> ::sqlx::encode::IsNull {
-                ::serde_json::to_value(self)
-                    .expect("must serialize json")
-                    .encode_by_ref(buf)
+                <::sqlx::types::JsonValue as ::sqlx::Encode<'__sqlx__EncodeDecode, ::sqlx::Postgres>>::encode_by_ref(
+                    &::serde_json::to_value(self).expect("must serialize json"),
+                    buf,
+                )
             }
         }
 
@@ -220,15 +380,15 @@ pub fn expand_derive_json_row(mut ast: DeriveInput) -> TokenStream {
             fn decode(
                 value: <::sqlx::Postgres as ::sqlx::database::HasValueRef<'__sqlx__EncodeDecode>>::ValueRef,
             ) -> ::std::result::Result<Self, ::sqlx::error::BoxDynError> {
-                Ok(::serde_json::from_value(::sqlx::types::JsonValue::decode(
-                    value,
-                )?)?)
+                Ok(::serde_json::from_value(
+                    <::sqlx::types::JsonValue as ::sqlx::Decode<'__sqlx__EncodeDecode, ::sqlx::Postgres>>::decode(value)?,
+                )?)
             }
         }
 
         impl #impl_generics_with_sqlx_lifetime ::sqlx::postgres::PgHasArrayType for #ident #ty_generics #where_clause {
             fn array_type_info() -> ::sqlx::postgres::PgTypeInfo {
-                ::sqlx::types::JsonValue::array_type_info()
+                <::sqlx::types::JsonValue as ::sqlx::postgres::PgHasArrayType>::array_type_info()
             }
         }
     };
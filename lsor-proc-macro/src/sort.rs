@@ -26,6 +26,9 @@ pub fn expand_derive_sort(input: TokenStream) -> TokenStream {
     let mut order_impls = Vec::new();
     let mut flip_impls = Vec::new();
     let mut push_to_driver_with_order_impls = Vec::new();
+    let mut push_to_driver_with_table_name_impls = Vec::new();
+    let mut push_to_driver_with_order_with_table_name_impls = Vec::new();
+    let mut is_key_nullable_impls = Vec::new();
     let mut cursor_impls = Vec::new();
 
     for field in fields.named.iter() {
@@ -42,6 +45,7 @@ pub fn expand_derive_sort(input: TokenStream) -> TokenStream {
         let field_ty = &field.ty;
 
         let flat = util::has_flatten_attr(&field.attrs);
+        let column_name = util::column_name(field, &ast.attrs);
 
         sort_ident_variant_decls
             .push(quote! { #field_ident_camel_case(<#field_ty as ::lsor::sort::Sortable>::Sort), });
@@ -56,13 +60,13 @@ pub fn expand_derive_sort(input: TokenStream) -> TokenStream {
         } else if has_json_attr {
             push_to_driver_impls.push(quote! {
                 #sort_ident::#field_ident_camel_case(sort) => {
-                    sort.push_to_driver_with_lhs(&::lsor::column::json(lhs).get(stringify!(#field_ident)), driver);
+                    sort.push_to_driver_with_lhs(&::lsor::column::json(lhs).get(#column_name), driver);
                 },
             });
         } else {
             push_to_driver_impls.push(quote! {
                 #sort_ident::#field_ident_camel_case(sort) => {
-                    sort.push_to_driver_with_lhs(&::lsor::column::col(stringify!(#field_ident)), driver);
+                    sort.push_to_driver_with_lhs(&::lsor::column::col(#column_name), driver);
                 },
             });
         }
@@ -83,16 +87,51 @@ pub fn expand_derive_sort(input: TokenStream) -> TokenStream {
         } else if has_json_attr {
             push_to_driver_with_order_impls.push(quote! {
                 #sort_ident::#field_ident_camel_case(sort) => {
-                    sort.push_to_driver_with_order_with_lhs(&::lsor::column::json(lhs).get(stringify!(#field_ident)), driver);
+                    sort.push_to_driver_with_order_with_lhs(&::lsor::column::json(lhs).get(#column_name), driver);
                 },
             });
         } else {
             push_to_driver_with_order_impls.push(quote! {
                 #sort_ident::#field_ident_camel_case(sort) => {
-                    sort.push_to_driver_with_order_with_lhs(&::lsor::column::col(stringify!(#field_ident)), driver);
+                    sort.push_to_driver_with_order_with_lhs(&::lsor::column::col(#column_name), driver);
                 },
             });
         }
+        // json sortings are never pushed on their own, so they never need to
+        // qualify their columns
+        if flat {
+            push_to_driver_with_table_name_impls.push(quote! {
+                #sort_ident::#field_ident_camel_case(sort) => {
+                    ::lsor::sort::Sorting::<__DB>::push_to_driver_with_table_name(sort, tn, driver);
+                },
+            });
+            push_to_driver_with_order_with_table_name_impls.push(quote! {
+                #sort_ident::#field_ident_camel_case(sort) => {
+                    ::lsor::sort::Sorting::<__DB>::push_to_driver_with_order_with_table_name(sort, tn, driver);
+                },
+            });
+        } else {
+            push_to_driver_with_table_name_impls.push(quote! {
+                #sort_ident::#field_ident_camel_case(sort) => {
+                    sort.push_to_driver_with_lhs(&::lsor::table::dot(tn, ::lsor::column::col(#column_name)), driver);
+                },
+            });
+            push_to_driver_with_order_with_table_name_impls.push(quote! {
+                #sort_ident::#field_ident_camel_case(sort) => {
+                    sort.push_to_driver_with_order_with_lhs(&::lsor::table::dot(tn, ::lsor::column::col(#column_name)), driver);
+                },
+            });
+        }
+        if flat {
+            is_key_nullable_impls.push(quote! {
+                #sort_ident::#field_ident_camel_case(sort) => ::lsor::sort::Sorting::<__DB>::is_key_nullable(sort, i),
+            });
+        } else {
+            let nullable = util::is_option_type(field_ty);
+            is_key_nullable_impls.push(quote! {
+                #sort_ident::#field_ident_camel_case(_) => #nullable,
+            });
+        }
         cursor_impls.push(quote! {
             #sort_ident::#field_ident_camel_case(x) => {
                 use ::lsor::cursor::Iterable;
@@ -103,58 +142,76 @@ pub fn expand_derive_sort(input: TokenStream) -> TokenStream {
 
     let trait_impls = if !has_json_attr {
         Some(quote! {
-            impl ::lsor::driver::PushPrql for #sort_ident {
-                fn push_to_driver(&self, driver: &mut ::lsor::driver::Driver) {
+            impl<__DB: ::lsor::backend::Backend> ::lsor::driver::PushPrql<__DB> for #sort_ident {
+                fn push_to_driver(&self, driver: &mut ::lsor::driver::Driver<__DB>) {
                     match &self {
                         #(#push_to_driver_impls)*
                     }
                 }
             }
 
-            impl ::lsor::sort::Sorting for #sort_ident {
+            impl<__DB: ::lsor::backend::Backend> ::lsor::sort::Sorting<__DB> for #sort_ident {
                 fn order(&self) -> ::lsor::sort::Order {
-                    match self {
-                        #(#order_impls)*
-                    }
+                    #sort_ident::order(self)
                 }
 
-                fn flip(&self) -> impl ::lsor::sort::Sorting {
+                fn flip(&self) -> impl ::lsor::sort::Sorting<__DB> {
                     match self {
                         #(#flip_impls)*
                     }
                 }
 
-                fn push_to_driver_with_order(&self, driver: &mut ::lsor::driver::Driver) {
+                fn push_to_driver_with_order(&self, driver: &mut ::lsor::driver::Driver<__DB>) {
                     match &self {
                         #(#push_to_driver_with_order_impls)*
                     }
                 }
+
+                fn is_key_nullable(&self, i: usize) -> bool {
+                    match &self {
+                        #(#is_key_nullable_impls)*
+                    }
+                }
+
+                fn push_to_driver_with_table_name(&self, tn: &dyn ::lsor::driver::PushPrql<__DB>, driver: &mut ::lsor::driver::Driver<__DB>) {
+                    match &self {
+                        #(#push_to_driver_with_table_name_impls)*
+                    }
+                }
+
+                fn push_to_driver_with_order_with_table_name(&self, tn: &dyn ::lsor::driver::PushPrql<__DB>, driver: &mut ::lsor::driver::Driver<__DB>) {
+                    match &self {
+                        #(#push_to_driver_with_order_with_table_name_impls)*
+                    }
+                }
+
+                fn push_key_to_driver_with_table_name(&self, _i: usize, tn: &dyn ::lsor::driver::PushPrql<__DB>, driver: &mut ::lsor::driver::Driver<__DB>) {
+                    ::lsor::sort::Sorting::<__DB>::push_to_driver_with_table_name(self, tn, driver)
+                }
             }
         })
     } else {
         None
     };
 
-    let non_trait_order_impl = if has_json_attr {
-        Some(quote! {
-            pub fn order(&self) -> ::lsor::sort::Order {
-                match self {
-                    #(#order_impls)*
-                }
+    // the order does not depend on the backend, so it is also implemented as an
+    // inherent method to save the callers from naming the backend
+    let non_trait_order_impl = quote! {
+        pub fn order(&self) -> ::lsor::sort::Order {
+            match self {
+                #(#order_impls)*
             }
-        })
-    } else {
-        None
+        }
     };
     let non_trait_impls = quote! {
         impl #sort_ident {
-            pub fn push_to_driver_with_lhs(&self, lhs: &dyn ::lsor::driver::PushPrql, driver: &mut ::lsor::driver::Driver) {
+            pub fn push_to_driver_with_lhs<__DB: ::lsor::backend::Backend>(&self, lhs: &dyn ::lsor::driver::PushPrql<__DB>, driver: &mut ::lsor::driver::Driver<__DB>) {
                 match &self {
                     #(#push_to_driver_impls)*
                 }
             }
 
-            pub fn push_to_driver_with_order_with_lhs(&self, lhs: &dyn ::lsor::driver::PushPrql, driver: &mut ::lsor::driver::Driver) {
+            pub fn push_to_driver_with_order_with_lhs<__DB: ::lsor::backend::Backend>(&self, lhs: &dyn ::lsor::driver::PushPrql<__DB>, driver: &mut ::lsor::driver::Driver<__DB>) {
                 match &self {
                     #(#push_to_driver_with_order_impls)*
                 }
@@ -172,6 +229,8 @@ pub fn expand_derive_sort(input: TokenStream) -> TokenStream {
 
     let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
 
+    let graphql = util::expand_graphql_oneof_object();
+
     let expanded = quote! {
         impl #impl_generics ::lsor::sort::Sortable for #ident #ty_generics #where_clause {
             type Sort = #sort_ident;
@@ -185,8 +244,8 @@ pub fn expand_derive_sort(input: TokenStream) -> TokenStream {
             }
         }
 
-        #[derive(::std::clone::Clone, ::std::fmt::Debug, ::async_graphql::OneofObject)]
-        #[graphql(rename_fields = "snake_case")]
+        #[derive(::std::clone::Clone, ::std::fmt::Debug)]
+        #graphql
         pub enum #sort_ident {
             #(#sort_ident_variant_decls)*
         }
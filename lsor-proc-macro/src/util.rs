@@ -1,5 +1,19 @@
-use proc_macro2::{Span, TokenTree};
-use syn::{Attribute, Ident};
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::quote;
+use syn::{Attribute, Field, Ident, Type};
+
+/// Derives a GraphQL input object for a generated filter or sort enum. Without
+/// the `graphql` feature, the enum is a plain Rust enum.
+pub(crate) fn expand_graphql_oneof_object() -> TokenStream {
+    if cfg!(feature = "graphql") {
+        quote! {
+            #[derive(::async_graphql::OneofObject)]
+            #[graphql(rename_fields = "snake_case")]
+        }
+    } else {
+        quote! {}
+    }
+}
 
 pub(crate) fn concat_idents(ident1: &Ident, ident2: &Ident) -> Ident {
     let combined = format!("{}{}", ident1, ident2);
@@ -41,7 +55,81 @@ pub(crate) fn camel_case_to_snake_case(s: &str) -> String {
     snake_case
 }
 
+/// The name of the column of a field. This is the `#[lsor(rename = "...")]`
+/// of the field if it has one, and otherwise the name of the field with the
+/// `#[lsor(rename_all = "...")]` rule of the struct applied.
+pub(crate) fn column_name(field: &Field, container_attrs: &[Attribute]) -> String {
+    let name = match collect_str_attr("rename", &field.attrs) {
+        Some(name) => name,
+        None => rename_all(field, container_attrs),
+    };
+    // columns are pushed into PRQL unquoted, so e.g. `display-name` would be
+    // read as a subtraction
+    if !is_ident(&name) {
+        panic!("column name must be an identifier: {}", name);
+    }
+    name
+}
+
+fn rename_all(field: &Field, container_attrs: &[Attribute]) -> String {
+    let name = field.ident.as_ref().unwrap().to_string();
+    match collect_str_attr("rename_all", container_attrs).as_deref() {
+        None | Some("snake_case") => name,
+        Some("lowercase") => name.to_lowercase(),
+        Some("UPPERCASE") => name.to_uppercase(),
+        Some("SCREAMING_SNAKE_CASE") => name.to_uppercase(),
+        Some("PascalCase") => snake_case_to_camel_case(&name),
+        Some("camelCase") => {
+            let pascal_case = snake_case_to_camel_case(&name);
+            let mut c = pascal_case.chars();
+            c.next()
+                .map(|f| f.to_lowercase().collect::<String>() + c.as_str())
+                .unwrap_or_default()
+        }
+        Some(rule) => panic!("unknown rename_all rule: {}", rule),
+    }
+}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Whether the type is an `Option`, i.e. whether its column can be NULL.
+pub(crate) fn is_option_type(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
 pub(crate) fn collect_table_attr(attrs: &[Attribute]) -> Option<String> {
+    collect_str_attr("table", attrs)
+}
+
+/// Collects the comma-separated columns of `#[lsor(unique = "a, b")]`.
+pub(crate) fn collect_unique_attr(attrs: &[Attribute]) -> Option<Vec<String>> {
+    collect_str_attr("unique", attrs).map(|columns| {
+        columns
+            .split(',')
+            .map(|column| column.trim().to_owned())
+            .collect()
+    })
+}
+
+pub(crate) fn collect_conflict_attr(attrs: &[Attribute]) -> Option<String> {
+    collect_str_attr("conflict", attrs)
+}
+
+/// Collects the string value of `#[lsor(name = "value")]`.
+fn collect_str_attr(name: &str, attrs: &[Attribute]) -> Option<String> {
     for attr in attrs {
         if !attr.path.is_ident("lsor") {
             // ignore non-lsor attributes
@@ -59,8 +147,8 @@ pub(crate) fn collect_table_attr(attrs: &[Attribute]) -> Option<String> {
             .flatten();
 
         while let Some(t) = token_iter.next() {
-            if t.to_string() != "table" {
-                // ignore non-table attributes
+            if t.to_string() != name {
+                // ignore other attributes
                 continue;
             }
             if let Some(t) = token_iter.next() {
@@ -69,7 +157,7 @@ pub(crate) fn collect_table_attr(attrs: &[Attribute]) -> Option<String> {
                     continue;
                 }
                 if let Some(t) = token_iter.next() {
-                    // collect the table name
+                    // collect the value
                     if t.to_string().starts_with('\"') && t.to_string().ends_with('\"') {
                         return Some(t.to_string()[1..t.to_string().len() - 1].to_owned());
                     }
@@ -118,6 +206,14 @@ pub(crate) fn has_pk_attr(attrs: &[Attribute]) -> bool {
     has_any_attr(&["pk", "primary_key"], attrs)
 }
 
+pub(crate) fn has_readonly_attr(attrs: &[Attribute]) -> bool {
+    has_any_attr(&["readonly"], attrs)
+}
+
+pub(crate) fn has_default_attr(attrs: &[Attribute]) -> bool {
+    has_any_attr(&["default"], attrs)
+}
+
 pub(crate) fn has_flatten_attr(attrs: &[Attribute]) -> bool {
     has_any_attr(&["flat", "flatten"], attrs)
 }
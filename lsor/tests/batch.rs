@@ -0,0 +1,67 @@
+//! lsor without GraphQL, e.g. in batch workers. These tests pass with and
+//! without the `graphql` feature.
+
+use lsor::{
+    driver::Driver,
+    exec::{load_page_raw, save_one},
+    filter::I32Filter,
+    page::{CountStrategy, Pagination},
+    sort::I32Sort,
+    Filter, Row, Sort,
+};
+use sqlx::{sqlite::SqlitePoolOptions, Sqlite};
+
+#[derive(Clone, Debug, Eq, Filter, PartialEq, Row, Sort)]
+#[lsor(table = "jobs")]
+pub struct Job {
+    #[lsor(pk)]
+    pub id: i64,
+    pub attempts: i32,
+}
+
+#[tokio::test]
+async fn test_batch_load_page_raw() {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::query("CREATE TABLE jobs (id INTEGER PRIMARY KEY, attempts INTEGER NOT NULL)")
+        .execute(&pool)
+        .await
+        .unwrap();
+    for (id, attempts) in [(1, 0), (2, 3), (3, 1)] {
+        save_one(Driver::<Sqlite>::empty(), &pool, Job { id, attempts })
+            .await
+            .unwrap();
+    }
+
+    // the derived filter and sort are plain enums
+    let filter = JobFilter::Attempts(I32Filter::Lt(3));
+    let sort = JobSort::Attempts(I32Sort::Desc);
+    let cursor = sort.cursor();
+    let page = load_page_raw::<_, _, _, _, Job>(
+        Driver::<Sqlite>::empty(),
+        &pool,
+        filter,
+        sort,
+        Pagination {
+            cursor,
+            after: None,
+            before: None,
+            first: Some(10),
+            last: None,
+            count: CountStrategy::Exact,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        page.items
+            .iter()
+            .map(|item| item.item.id)
+            .collect::<Vec<_>>(),
+        vec![3, 1]
+    );
+    assert_eq!(page.total_count, 2);
+}
@@ -1,11 +1,14 @@
+#![cfg(feature = "graphql")]
+
 use async_graphql::Enum;
 use chrono::{DateTime, Utc};
 use lsor::{
     column::col,
     driver::{Driver, PushPrql},
-    filter::{DateTimeFilter, I32Filter, UuidFilter},
-    row::upsert,
-    sort::{DateTimeSort, I32Sort, Sorting, StringSort, UuidSort},
+    filter::{DateTimeFilter, I32Filter, StringFilter, UuidFilter},
+    row::{upsert, upsert_many, Column, UpsertMode},
+    sort::{DateTimeSort, I32Sort, MultiSort, Order, Sorting, StringSort, UuidSort},
+    sql::{insert, update, PushSql},
     Filter, Row, Sort, Type,
 };
 use serde::{Deserialize, Serialize};
@@ -74,6 +77,59 @@ pub struct JsonAccount {
     pub metadata: Metadata,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq, Row)]
+#[lsor(table = "memberships")]
+pub struct Membership {
+    #[lsor(pk)]
+    pub user_id: Uuid,
+    #[lsor(pk)]
+    pub team_id: Uuid,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Row)]
+#[lsor(table = "subscribers", unique = "list, email")]
+pub struct Subscriber {
+    #[lsor(pk)]
+    pub id: Uuid,
+    pub list: String,
+    pub email: String,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Row)]
+#[lsor(table = "subscribers", conflict = "subscribers_list_email_key")]
+pub struct NamedSubscriber {
+    #[lsor(pk)]
+    pub id: Uuid,
+    pub list: String,
+    pub email: String,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Row)]
+#[lsor(table = "posts")]
+pub struct Post {
+    #[lsor(pk)]
+    pub id: i64,
+    pub title: String,
+    #[lsor(readonly)]
+    pub slug: String,
+    #[lsor(default)]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Eq, Filter, PartialEq, Row, Sort)]
+#[lsor(table = "profiles", rename_all = "camelCase")]
+pub struct Profile {
+    #[lsor(pk)]
+    pub id: Uuid,
+    pub display_name: String,
+    #[lsor(rename = "emailAddress")]
+    pub email: String,
+    #[lsor(rename = "age_in_years")]
+    pub age: i32,
+}
+
 #[test]
 fn test_enum_filter() {
     let mut driver = Driver::new();
@@ -162,6 +218,32 @@ fn test_struct_sort() {
     let mut driver = Driver::new();
     AccountSort::Id(UuidSort::Desc).push_to_driver_with_order(&mut driver);
     assert_eq!(driver.prql(), "-id");
+
+    let mut driver = Driver::new();
+    AccountSort::Id(UuidSort::Asc).push_to_driver_with_order(&mut driver);
+    assert_eq!(driver.prql(), "id");
+
+    assert_eq!(AccountSort::Id(UuidSort::Asc).order(), Order::Asc);
+    assert_eq!(AccountSort::Id(UuidSort::Desc).order(), Order::Desc);
+    assert_eq!(
+        AccountSort::Metadata(MetadataSort::CreatedAt(DateTimeSort::Asc)).order(),
+        Order::Asc
+    );
+}
+
+#[test]
+fn test_sort_nullable_keys() {
+    fn is_key_nullable(sort: AccountSort) -> bool {
+        Sorting::<sqlx::Postgres>::is_key_nullable(&sort, 0)
+    }
+
+    assert!(!is_key_nullable(AccountSort::Id(UuidSort::Asc)));
+    assert!(!is_key_nullable(AccountSort::Metadata(
+        MetadataSort::CreatedAt(DateTimeSort::Asc)
+    )));
+    assert!(is_key_nullable(AccountSort::Metadata(
+        MetadataSort::DeletedAt(DateTimeSort::Asc)
+    )));
 }
 
 #[test]
@@ -176,6 +258,25 @@ fn test_embedded_sort() {
     assert_eq!(driver.prql(), "-created_at");
 }
 
+#[test]
+fn test_multi_sort() {
+    use async_graphql::InputType;
+
+    let sort = MultiSort::from(vec![
+        AccountSort::Metadata(MetadataSort::CreatedAt(DateTimeSort::Desc)),
+        AccountSort::Id(UuidSort::Desc),
+    ]);
+
+    let mut driver = Driver::new();
+    sort.push_to_driver_with_order(&mut driver);
+    assert_eq!(driver.prql(), "-created_at, -id");
+
+    assert_eq!(
+        <MultiSort<AccountSort> as InputType>::type_name(),
+        "[AccountSort!]"
+    );
+}
+
 #[test]
 fn test_json_sort() {
     let mut driver = Driver::new();
@@ -209,6 +310,229 @@ fn test_upsert() {
     .push_to_driver(&mut driver);
     assert_eq!(
         driver.prql(),
-        "INSERT INTO accounts (id, tier, tiers, config, created_at, updated_at, deleted_at) VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (id) DO UPDATE SET (tier, tiers, config, created_at, updated_at, deleted_at) = ($2, $3, $4, $5, $6, $7)"
+        "INSERT INTO accounts (\"id\", \"tier\", \"tiers\", \"config\", \"created_at\", \"updated_at\", \"deleted_at\") VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (\"id\") DO UPDATE SET \"tier\" = EXCLUDED.\"tier\", \"tiers\" = EXCLUDED.\"tiers\", \"config\" = EXCLUDED.\"config\", \"created_at\" = EXCLUDED.\"created_at\", \"updated_at\" = EXCLUDED.\"updated_at\", \"deleted_at\" = EXCLUDED.\"deleted_at\""
+    );
+}
+
+#[test]
+fn test_upsert_returning() {
+    let mut driver = Driver::new();
+    upsert(Account {
+        id: Uuid::new_v4(),
+        tier: AccountTier::Free,
+        tiers: vec![],
+        config: AccountConfig {
+            x: 1,
+            y: "hello".to_string(),
+            z: Uuid::max(),
+        },
+        metadata: Metadata {
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: None,
+        },
+    })
+    .returning()
+    .push_to_driver(&mut driver);
+    assert_eq!(
+        driver.prql(),
+        "INSERT INTO accounts (\"id\", \"tier\", \"tiers\", \"config\", \"created_at\", \"updated_at\", \"deleted_at\") VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (\"id\") DO UPDATE SET \"tier\" = EXCLUDED.\"tier\", \"tiers\" = EXCLUDED.\"tiers\", \"config\" = EXCLUDED.\"config\", \"created_at\" = EXCLUDED.\"created_at\", \"updated_at\" = EXCLUDED.\"updated_at\", \"deleted_at\" = EXCLUDED.\"deleted_at\" RETURNING *, (xmax = 0) AS lsor_inserted"
+    );
+}
+
+#[test]
+fn test_upsert_many() {
+    let account = |id| Account {
+        id,
+        tier: AccountTier::Free,
+        tiers: vec![],
+        config: AccountConfig {
+            x: 1,
+            y: "hello".to_string(),
+            z: Uuid::max(),
+        },
+        metadata: Metadata {
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: None,
+        },
+    };
+
+    let mut driver = Driver::new();
+    upsert_many([account(Uuid::nil()), account(Uuid::max())]).push_to_driver(&mut driver);
+    assert_eq!(
+        driver.prql(),
+        "INSERT INTO accounts (\"id\", \"tier\", \"tiers\", \"config\", \"created_at\", \"updated_at\", \"deleted_at\") VALUES ($1, $2, $3, $4, $5, $6, $7), ($8, $9, $10, $11, $12, $13, $14) ON CONFLICT (\"id\") DO UPDATE SET \"tier\" = EXCLUDED.\"tier\", \"tiers\" = EXCLUDED.\"tiers\", \"config\" = EXCLUDED.\"config\", \"created_at\" = EXCLUDED.\"created_at\", \"updated_at\" = EXCLUDED.\"updated_at\", \"deleted_at\" = EXCLUDED.\"deleted_at\""
+    );
+
+    // 65535 binds fit 9362 rows of 7 columns
+    let upsert = upsert_many((0..10_000).map(|_| account(Uuid::new_v4())));
+    let chunks = upsert
+        .chunks::<sqlx::Postgres>()
+        .map(|chunk| chunk.rows.len())
+        .collect::<Vec<_>>();
+    assert_eq!(chunks, vec![9362, 638]);
+}
+
+#[test]
+fn test_upsert_conflict_targets() {
+    let mut driver = Driver::new();
+    upsert(Membership {
+        user_id: Uuid::nil(),
+        team_id: Uuid::max(),
+    })
+    .push_to_driver(&mut driver);
+    assert_eq!(
+        driver.prql(),
+        "INSERT INTO memberships (\"user_id\", \"team_id\") VALUES ($1, $2) ON CONFLICT (\"user_id\", \"team_id\") DO NOTHING"
+    );
+
+    let subscriber = Subscriber {
+        id: Uuid::nil(),
+        list: "news".to_string(),
+        email: "a@lsor.dev".to_string(),
+        name: "A".to_string(),
+    };
+
+    let mut driver = Driver::new();
+    upsert(&subscriber).push_to_driver(&mut driver);
+    assert_eq!(
+        driver.prql(),
+        "INSERT INTO subscribers (\"id\", \"list\", \"email\", \"name\") VALUES ($1, $2, $3, $4) ON CONFLICT (\"list\", \"email\") DO UPDATE SET \"name\" = EXCLUDED.\"name\""
+    );
+
+    let mut driver = Driver::new();
+    upsert(&subscriber)
+        .mode(UpsertMode::DoNothing)
+        .push_to_driver(&mut driver);
+    assert_eq!(
+        driver.prql(),
+        "INSERT INTO subscribers (\"id\", \"list\", \"email\", \"name\") VALUES ($1, $2, $3, $4) ON CONFLICT (\"list\", \"email\") DO NOTHING"
+    );
+
+    let mut driver = Driver::new();
+    upsert(NamedSubscriber {
+        id: subscriber.id,
+        list: subscriber.list.clone(),
+        email: subscriber.email.clone(),
+        name: subscriber.name.clone(),
+    })
+    .mode(UpsertMode::UpdateColumns(&["email", "name"]))
+    .push_to_driver(&mut driver);
+    assert_eq!(
+        driver.prql(),
+        "INSERT INTO subscribers (\"id\", \"list\", \"email\", \"name\") VALUES ($1, $2, $3, $4) ON CONFLICT ON CONSTRAINT \"subscribers_list_email_key\" DO UPDATE SET \"email\" = EXCLUDED.\"email\", \"name\" = EXCLUDED.\"name\""
+    );
+
+    // names that are not columns of the row are ignored
+    let mut driver = Driver::new();
+    upsert(&subscriber)
+        .mode(UpsertMode::UpdateColumns(&["nmae"]))
+        .push_to_driver(&mut driver);
+    assert_eq!(
+        driver.prql(),
+        "INSERT INTO subscribers (\"id\", \"list\", \"email\", \"name\") VALUES ($1, $2, $3, $4) ON CONFLICT (\"list\", \"email\") DO NOTHING"
+    );
+}
+
+#[test]
+fn test_readonly_and_default_columns() {
+    assert_eq!(
+        <Post as lsor::row::Row>::column_names().collect::<Vec<_>>(),
+        vec![
+            Column::new(col("id")).pk(),
+            Column::new(col("title")),
+            Column::new(col("slug")).readonly(),
+            Column::new(col("created_at")).default(),
+        ]
+    );
+
+    let mut driver = Driver::new();
+    upsert(Post {
+        id: 1,
+        title: "Hello".to_string(),
+        slug: "hello".to_string(),
+        created_at: Utc::now(),
+    })
+    .push_to_driver(&mut driver);
+    assert_eq!(
+        driver.prql(),
+        "INSERT INTO posts (\"id\", \"title\") VALUES ($1, $2) ON CONFLICT (\"id\") DO UPDATE SET \"title\" = EXCLUDED.\"title\""
+    );
+
+    // readonly and defaulted columns are never updated
+    let mut driver = Driver::new();
+    upsert(Post {
+        id: 1,
+        title: "Hello".to_string(),
+        slug: "hello".to_string(),
+        created_at: Utc::now(),
+    })
+    .mode(UpsertMode::UpdateColumns(&["title", "slug", "created_at"]))
+    .push_to_driver(&mut driver);
+    assert_eq!(
+        driver.prql(),
+        "INSERT INTO posts (\"id\", \"title\") VALUES ($1, $2) ON CONFLICT (\"id\") DO UPDATE SET \"title\" = EXCLUDED.\"title\""
+    );
+}
+
+#[test]
+fn test_renamed_columns() {
+    assert_eq!(
+        <Profile as lsor::row::Row>::column_names()
+            .map(|column| column.name)
+            .collect::<Vec<_>>(),
+        vec![
+            col("id"),
+            col("displayName"),
+            col("emailAddress"),
+            col("age_in_years"),
+        ]
+    );
+
+    let mut driver = Driver::new();
+    PushPrql::push_to_driver(
+        &ProfileFilter::DisplayName(StringFilter::Eq("A".to_string())),
+        &mut driver,
+    );
+    assert_eq!(driver.prql(), "profiles.displayName == $1");
+
+    let mut driver = Driver::new();
+    PushPrql::push_to_driver(&ProfileFilter::Age(I32Filter::Gt(17)), &mut driver);
+    assert_eq!(driver.prql(), "profiles.age_in_years > $1");
+
+    let mut driver = Driver::new();
+    ProfileSort::Email(StringSort::Desc).push_to_driver_with_order(&mut driver);
+    assert_eq!(driver.prql(), "-emailAddress");
+}
+
+#[test]
+fn test_renamed_columns_in_statements() {
+    let profile = Profile {
+        id: Uuid::nil(),
+        display_name: "A".to_string(),
+        email: "a@lsor.dev".to_string(),
+        age: 18,
+    };
+
+    let mut driver = Driver::new();
+    insert(profile.clone()).push_sql(&mut driver).unwrap();
+    assert_eq!(
+        driver.prql(),
+        "INSERT INTO profiles (\"id\", \"displayName\", \"emailAddress\", \"age_in_years\") VALUES ($1, $2, $3, $4)"
+    );
+
+    let mut driver = Driver::new();
+    update(profile.clone()).push_sql(&mut driver).unwrap();
+    assert_eq!(
+        driver.prql(),
+        "UPDATE profiles SET \"displayName\" = $2, \"emailAddress\" = $3, \"age_in_years\" = $4 WHERE \"id\" = $1"
+    );
+
+    let mut driver = Driver::new();
+    upsert(profile).push_to_driver(&mut driver);
+    assert_eq!(
+        driver.prql(),
+        "INSERT INTO profiles (\"id\", \"displayName\", \"emailAddress\", \"age_in_years\") VALUES ($1, $2, $3, $4) ON CONFLICT (\"id\") DO UPDATE SET \"displayName\" = EXCLUDED.\"displayName\", \"emailAddress\" = EXCLUDED.\"emailAddress\", \"age_in_years\" = EXCLUDED.\"age_in_years\""
     );
 }
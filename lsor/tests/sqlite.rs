@@ -0,0 +1,1130 @@
+#![cfg(feature = "graphql")]
+
+use async_graphql::SimpleObject;
+use futures_util::TryStreamExt;
+use lsor::{
+    aggregate::Aggregate,
+    column::col,
+    cond::eq,
+    cursor::{Cursor, CursorSigner},
+    driver::{Driver, PushPrql},
+    exec::{
+        delete_many, insert_one, load_offset_page, load_one, load_page, load_page_raw, save_many,
+        save_one, scan, update_many, update_one,
+    },
+    expr::{concat, count, count_distinct, length, max, modulo, mul, starts_with, upper},
+    filter::{I32Filter, I64Filter, StringFilter},
+    from::from,
+    join::{select_joined, JoinSide, Joined},
+    page::{CountStrategy, OffsetPagination, Pagination},
+    row::{upsert, UpsertMode},
+    select::{alias, select_from},
+    sort::{multi_sort, qualified, I64Sort},
+    table::{dot, Table},
+    Filter, Row, Sort,
+};
+use serde::Serialize;
+use sqlx::{sqlite::SqlitePoolOptions, Sqlite, SqlitePool};
+
+#[derive(Clone, Debug, Eq, Filter, PartialEq, Row, Serialize, SimpleObject, Sort)]
+#[lsor(table = "users")]
+pub struct User {
+    #[lsor(pk)]
+    pub id: i64,
+    pub email: String,
+    pub age: i32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Row, SimpleObject)]
+#[lsor(table = "scores")]
+pub struct Score {
+    #[lsor(pk)]
+    pub id: i64,
+    pub score: Option<i32>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Row)]
+#[lsor(table = "subscribers", unique = "email")]
+pub struct Subscriber {
+    #[lsor(pk)]
+    pub id: i64,
+    pub email: String,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Row)]
+#[lsor(table = "posts")]
+pub struct Post {
+    #[lsor(default)]
+    pub id: i64,
+    pub title: String,
+    #[lsor(readonly)]
+    pub slug: String,
+}
+
+#[derive(Clone, Debug, Eq, Filter, PartialEq, Row, Sort)]
+#[lsor(table = "profiles", rename_all = "camelCase")]
+pub struct Profile {
+    #[lsor(pk)]
+    pub id: i64,
+    pub display_name: String,
+    #[lsor(rename = "years")]
+    pub age: i32,
+}
+
+#[derive(Clone, Debug, Eq, Filter, PartialEq, Row, Sort)]
+#[lsor(table = "tokens")]
+pub struct Token {
+    #[lsor(pk)]
+    pub id: i64,
+    pub user_id: i64,
+    pub value: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Row)]
+pub struct UserStats {
+    pub adult: bool,
+    pub n: i64,
+    pub emails: i64,
+    pub oldest: i32,
+}
+
+async fn pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::query(
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT NOT NULL, age INTEGER NOT NULL)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    for (id, email, age) in [
+        (1, "a@lsor.dev", 17),
+        (2, "b@lsor.dev", 21),
+        (3, "c@lsor.dev", 42),
+    ] {
+        save_one(
+            Driver::<Sqlite>::empty(),
+            &pool,
+            User {
+                id,
+                email: email.to_string(),
+                age,
+            },
+        )
+        .await
+        .unwrap();
+    }
+    pool
+}
+
+#[tokio::test]
+async fn test_sqlite_load_one() {
+    let pool = pool().await;
+
+    let user: Option<User> = load_one(
+        Driver::<Sqlite>::empty(),
+        &pool,
+        UserFilter::All(vec![
+            UserFilter::Age(I32Filter::Gt(18)),
+            UserFilter::Email(StringFilter::Eq("c@lsor.dev".to_string())),
+        ]),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        user,
+        Some(User {
+            id: 3,
+            email: "c@lsor.dev".to_string(),
+            age: 42,
+        })
+    );
+
+    let user: Option<User> = load_one(
+        Driver::<Sqlite>::empty(),
+        &pool,
+        UserFilter::Age(I32Filter::Gt(42)),
+    )
+    .await
+    .unwrap();
+    assert_eq!(user, None);
+}
+
+#[tokio::test]
+async fn test_sqlite_save_one_updates() {
+    let pool = pool().await;
+
+    save_one(
+        Driver::<Sqlite>::empty(),
+        &pool,
+        User {
+            id: 1,
+            email: "a@lsor.dev".to_string(),
+            age: 18,
+        },
+    )
+    .await
+    .unwrap();
+
+    let user: Option<User> = load_one(
+        Driver::<Sqlite>::empty(),
+        &pool,
+        UserFilter::Email(StringFilter::Eq("a@lsor.dev".to_string())),
+    )
+    .await
+    .unwrap();
+    assert_eq!(user.map(|user| user.age), Some(18));
+}
+
+#[derive(Row)]
+pub struct UserAge {
+    pub age: i32,
+}
+
+#[tokio::test]
+async fn test_sqlite_insert_update_and_delete() {
+    let pool = pool().await;
+    let user = |id, age| User {
+        id,
+        email: format!("{}@lsor.dev", id),
+        age,
+    };
+    let ages = |pool| async move {
+        let mut driver = Driver::<Sqlite>::empty();
+        from(User::table_name())
+            .sort(UserSort::Id(I64Sort::Asc))
+            .push_to_driver(&mut driver);
+        let users: Vec<User> = driver.fetch_all_as(pool).await.unwrap();
+        users
+            .into_iter()
+            .map(|user| (user.id, user.age))
+            .collect::<Vec<_>>()
+    };
+
+    let inserted = insert_one(Driver::<Sqlite>::empty(), &pool, user(4, 50))
+        .await
+        .unwrap();
+    assert_eq!(inserted, 1);
+    // inserting an existing row fails instead of updating it
+    let err = insert_one(Driver::<Sqlite>::empty(), &pool, user(4, 60))
+        .await
+        .unwrap_err();
+    assert!(err.is_database());
+
+    let updated = update_one(Driver::<Sqlite>::empty(), &pool, user(4, 60))
+        .await
+        .unwrap();
+    assert_eq!(updated, 1);
+    let updated = update_one(Driver::<Sqlite>::empty(), &pool, user(5, 60))
+        .await
+        .unwrap();
+    assert_eq!(updated, 0);
+    assert_eq!(ages(&pool).await, [(1, 17), (2, 21), (3, 42), (4, 60)]);
+
+    let updated = update_many::<_, _, _, _, User>(
+        Driver::<Sqlite>::empty(),
+        &pool,
+        UserFilter::Age(I32Filter::Gt(18)),
+        UserAge { age: 18 },
+    )
+    .await
+    .unwrap();
+    assert_eq!(updated, 3);
+    assert_eq!(ages(&pool).await, [(1, 17), (2, 18), (3, 18), (4, 18)]);
+
+    let deleted = delete_many::<_, _, _, User>(
+        Driver::<Sqlite>::empty(),
+        &pool,
+        UserFilter::Age(I32Filter::Eq(18)),
+    )
+    .await
+    .unwrap();
+    assert_eq!(deleted, 3);
+    assert_eq!(ages(&pool).await, [(1, 17)]);
+}
+
+#[tokio::test]
+async fn test_sqlite_upsert_on_unique_columns() {
+    let pool = pool().await;
+    sqlx::query(
+        "CREATE TABLE subscribers (id INTEGER PRIMARY KEY, email TEXT NOT NULL UNIQUE, name TEXT NOT NULL)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    let subscriber = |id, name: &str| Subscriber {
+        id,
+        email: "a@lsor.dev".to_string(),
+        name: name.to_string(),
+    };
+    let subscribers = |pool| async move {
+        let mut driver = Driver::<Sqlite>::empty();
+        from(Subscriber::table_name()).push_to_driver(&mut driver);
+        let subscribers: Vec<Subscriber> = driver.fetch_all_as(pool).await.unwrap();
+        subscribers
+    };
+
+    save_one(Driver::<Sqlite>::empty(), &pool, subscriber(1, "A"))
+        .await
+        .unwrap();
+    // conflicts on the email, and keeps the id of the existing row
+    save_one(Driver::<Sqlite>::empty(), &pool, subscriber(2, "B"))
+        .await
+        .unwrap();
+    assert_eq!(subscribers(&pool).await, [subscriber(1, "B")]);
+
+    let mut driver = Driver::<Sqlite>::empty();
+    upsert(subscriber(3, "C"))
+        .mode(UpsertMode::DoNothing)
+        .push_to_driver(&mut driver);
+    driver.execute_without_compilation(&pool).await.unwrap();
+    assert_eq!(subscribers(&pool).await, [subscriber(1, "B")]);
+}
+
+#[tokio::test]
+async fn test_sqlite_readonly_and_default_columns() {
+    let pool = pool().await;
+    sqlx::query(
+        "CREATE TABLE posts (id INTEGER PRIMARY KEY, title TEXT NOT NULL, slug TEXT GENERATED ALWAYS AS (lower(title)))",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    for title in ["Hello", "World"] {
+        let post = Post {
+            id: 0,
+            title: title.to_string(),
+            slug: String::new(),
+        };
+        insert_one(Driver::<Sqlite>::empty(), &pool, post)
+            .await
+            .unwrap();
+    }
+
+    let mut driver = Driver::<Sqlite>::empty();
+    from(Post::table_name()).push_to_driver(&mut driver);
+    let posts: Vec<Post> = driver.fetch_all_as(&pool).await.unwrap();
+    assert_eq!(
+        posts,
+        [
+            Post {
+                id: 1,
+                title: "Hello".to_string(),
+                slug: "hello".to_string(),
+            },
+            Post {
+                id: 2,
+                title: "World".to_string(),
+                slug: "world".to_string(),
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_sqlite_renamed_columns() {
+    let pool = pool().await;
+    sqlx::query(
+        "CREATE TABLE profiles (id INTEGER PRIMARY KEY, displayName TEXT NOT NULL, years INTEGER NOT NULL)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    for (id, display_name, age) in [(1, "A", 17), (2, "B", 21)] {
+        save_one(
+            Driver::<Sqlite>::empty(),
+            &pool,
+            Profile {
+                id,
+                display_name: display_name.to_string(),
+                age,
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    let profile: Option<Profile> = load_one(
+        Driver::<Sqlite>::empty(),
+        &pool,
+        ProfileFilter::All(vec![
+            ProfileFilter::Age(I32Filter::Gt(18)),
+            ProfileFilter::DisplayName(StringFilter::Eq("B".to_string())),
+        ]),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        profile,
+        Some(Profile {
+            id: 2,
+            display_name: "B".to_string(),
+            age: 21,
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_sqlite_save_many() {
+    let pool = pool().await;
+
+    // more rows than fit into one statement, some of which already exist
+    let users = (1..=12_000)
+        .map(|id| User {
+            id,
+            email: format!("{}@lsor.dev", id),
+            age: 30,
+        })
+        .collect::<Vec<_>>();
+    let total = || async {
+        let mut driver = Driver::<Sqlite>::empty();
+        Aggregate {
+            query: from(User::table_name()).filter(UserFilter::Age(I32Filter::Eq(30))),
+            aggregations: vec![(col("total"), count())],
+        }
+        .push_to_driver(&mut driver);
+        driver.fetch_scalar::<i64>(&pool).await.unwrap()
+    };
+
+    // the rows are saved in a transaction of their own, or in the caller's
+    let mut tx = pool.begin().await.unwrap();
+    save_many(Driver::<Sqlite>::empty(), &mut *tx, users.clone())
+        .await
+        .unwrap();
+    tx.rollback().await.unwrap();
+    assert_eq!(total().await, 0);
+
+    save_many(Driver::<Sqlite>::empty(), &pool, users)
+        .await
+        .unwrap();
+    assert_eq!(total().await, 12_000);
+}
+
+#[tokio::test]
+async fn test_sqlite_fetch_as() {
+    let pool = pool().await;
+    let adults = || {
+        let mut driver = Driver::<Sqlite>::empty();
+        from(User::table_name())
+            .filter(UserFilter::Age(I32Filter::Gt(18)))
+            .sort(UserSort::Id(I64Sort::Asc))
+            .push_to_driver(&mut driver);
+        driver
+    };
+
+    let users: Vec<User> = adults().fetch_all_as(&pool).await.unwrap();
+    assert_eq!(users.iter().map(|user| user.id).collect::<Vec<_>>(), [2, 3]);
+
+    let user: User = adults().fetch_one_as(&pool).await.unwrap();
+    assert_eq!(user.email, "b@lsor.dev");
+
+    let user: Option<(i64, String)> = adults().fetch_optional_as(&pool).await.unwrap();
+    assert_eq!(user, Some((2, "b@lsor.dev".to_string())));
+
+    let users: Vec<User> = adults().fetch_stream_as(&pool).try_collect().await.unwrap();
+    assert_eq!(
+        users.iter().map(|user| user.age).collect::<Vec<_>>(),
+        [21, 42]
+    );
+
+    let mut driver = Driver::<Sqlite>::empty();
+    Aggregate {
+        query: from(User::table_name()),
+        aggregations: vec![(col("total"), count())],
+    }
+    .push_to_driver(&mut driver);
+    let total: i64 = driver.fetch_scalar(&pool).await.unwrap();
+    assert_eq!(total, 3);
+}
+
+#[tokio::test]
+async fn test_sqlite_join() {
+    let pool = pool().await;
+    sqlx::query(
+        "CREATE TABLE tokens (id INTEGER PRIMARY KEY, user_id INTEGER NOT NULL, value TEXT NOT NULL)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    for (id, user_id, value) in [(1, 2, "x"), (2, 3, "y"), (3, 3, "z")] {
+        save_one(
+            Driver::<Sqlite>::empty(),
+            &pool,
+            Token {
+                id,
+                user_id,
+                value: value.to_string(),
+            },
+        )
+        .await
+        .unwrap();
+    }
+    let on = || {
+        eq(
+            dot(User::table_name(), col("id")),
+            dot(Token::table_name(), col("user_id")),
+        )
+    };
+
+    // both tables have an `id` column, so the sorts are qualified
+    let mut driver = Driver::<Sqlite>::empty();
+    select_joined::<User, Token, _>(
+        from(User::table_name())
+            .join(JoinSide::Inner, Token::table_name(), on())
+            .filter(TokenFilter::Value(StringFilter::Ne("x".to_string())))
+            .sort(qualified(Token::table_name(), TokenSort::Id(I64Sort::Desc))),
+    )
+    .push_to_driver(&mut driver);
+    let rows: Vec<Joined<User, Token>> = driver.fetch_all_as(&pool).await.unwrap();
+    let rows = rows.into_iter().map(Joined::into_inner).collect::<Vec<_>>();
+    assert_eq!(
+        rows.iter()
+            .map(|(user, token)| (user.id, token.id, token.value.as_str()))
+            .collect::<Vec<_>>(),
+        [(3, 3, "z"), (3, 2, "y")]
+    );
+
+    // users without tokens are kept by a left join
+    let mut driver = Driver::<Sqlite>::empty();
+    select_joined::<User, Token, _>(
+        from(User::table_name())
+            .join(JoinSide::Left, Token::table_name(), on())
+            .filter(UserFilter::Age(I32Filter::Lt(30)))
+            .sort(qualified(User::table_name(), UserSort::Id(I64Sort::Asc))),
+    )
+    .push_to_driver(&mut driver);
+    let rows: Vec<Joined<User, Option<Token>>> = driver.fetch_all_as(&pool).await.unwrap();
+    assert_eq!(
+        rows.iter()
+            .map(|Joined(user, token)| (user.id, token.as_ref().map(|token| token.id)))
+            .collect::<Vec<_>>(),
+        [(1, None), (2, Some(1))]
+    );
+}
+
+#[tokio::test]
+async fn test_sqlite_select() {
+    let pool = pool().await;
+    sqlx::query("ALTER TABLE users ADD COLUMN avatar BLOB")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    // the row is read from its own columns, whatever else the table has
+    let mut driver = Driver::<Sqlite>::empty();
+    from(User::table_name())
+        .filter(UserFilter::Age(I32Filter::Gt(18)))
+        .select_row::<User>()
+        .sort(UserSort::Id(I64Sort::Asc))
+        .push_to_driver(&mut driver);
+    assert_eq!(
+        driver.sql(),
+        "SELECT id, email, age FROM users WHERE age > ?1 ORDER BY id"
+    );
+    let users: Vec<User> = driver.fetch_all_as(&pool).await.unwrap();
+    assert_eq!(users.iter().map(|user| user.id).collect::<Vec<_>>(), [2, 3]);
+
+    let mut driver = Driver::<Sqlite>::empty();
+    select_from(
+        from(User::table_name()).filter(UserFilter::Id(I64Filter::Eq(3))),
+        vec![&col("email"), &alias("is_adult", col("age").gt(18))],
+    )
+    .push_to_driver(&mut driver);
+    let user: (String, bool) = driver.fetch_one_as(&pool).await.unwrap();
+    assert_eq!(user, ("c@lsor.dev".to_string(), true));
+}
+
+#[tokio::test]
+async fn test_sqlite_group() {
+    let pool = pool().await;
+
+    let mut driver = Driver::<Sqlite>::empty();
+    let (n, emails, oldest) = (count(), count_distinct(col("email")), max(col("age")));
+    from(User::table_name())
+        .derive("adult", col("age").gt(18))
+        .group([col("adult")], |g| {
+            g.aggregate("n", &n as &dyn PushPrql<Sqlite>)
+                .aggregate("emails", &emails)
+                .aggregate("oldest", &oldest)
+        })
+        .select_row::<UserStats>()
+        .sort(col("adult").asc())
+        .push_to_driver(&mut driver);
+    let stats: Vec<UserStats> = driver.fetch_all_as(&pool).await.unwrap();
+    assert_eq!(
+        stats,
+        [
+            UserStats {
+                adult: false,
+                n: 1,
+                emails: 1,
+                oldest: 17,
+            },
+            UserStats {
+                adult: true,
+                n: 2,
+                emails: 2,
+                oldest: 42,
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_sqlite_expressions() {
+    let pool = pool().await;
+
+    let mut driver = Driver::<Sqlite>::empty();
+    let (name, doubled, odd) = (
+        upper(col("email")),
+        mul(col("age"), 2),
+        modulo(col("age"), 2),
+    );
+    select_from(
+        from(User::table_name())
+            .filter(starts_with(col("email"), "b") | col("age").eq(42))
+            .derive("handle", concat([col("email"), col("email")]))
+            .sort(col("id").asc()),
+        vec![
+            &alias("name", &name) as &dyn PushPrql<Sqlite>,
+            &alias("length", length(col("handle"))),
+            &alias("doubled", &doubled),
+            &alias("odd", &odd),
+        ],
+    )
+    .push_to_driver(&mut driver);
+    let rows: Vec<(String, i64, i32, i32)> = driver.fetch_all_as(&pool).await.unwrap();
+    assert_eq!(
+        rows,
+        [
+            ("B@LSOR.DEV".to_string(), 20, 42, 1),
+            ("C@LSOR.DEV".to_string(), 20, 84, 0),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_sqlite_load_page() {
+    let pool = pool().await;
+
+    let conn = load_page::<_, _, _, _, User>(
+        Driver::<Sqlite>::empty(),
+        &pool,
+        UserFilter::Age(I32Filter::Gt(0)),
+        col("id").asc(),
+        Pagination {
+            cursor: Cursor::I64,
+            after: None,
+            before: None,
+            first: Some(2),
+            last: None,
+            count: CountStrategy::Exact,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(conn.edges.len(), 2);
+    assert_eq!(conn.additional_fields.total_count, 3);
+    assert!(conn.has_next_page);
+    assert!(!conn.has_previous_page);
+
+    // rows are only counted when asked for
+    let conn = load_page::<_, _, _, _, User>(
+        Driver::<Sqlite>::empty(),
+        &pool,
+        UserFilter::Age(I32Filter::Gt(0)),
+        col("id").asc(),
+        Pagination {
+            cursor: Cursor::I64,
+            after: conn.edges.last().map(|edge| edge.cursor.clone()),
+            before: None,
+            first: Some(2),
+            last: None,
+            count: CountStrategy::None,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(conn.edges.len(), 1);
+    assert_eq!(conn.additional_fields.total_count, 0);
+    assert!(!conn.has_next_page);
+    assert!(conn.has_previous_page);
+
+    // SQLite cannot estimate rows, so they are counted instead
+    let conn = load_page::<_, _, _, _, User>(
+        Driver::<Sqlite>::empty(),
+        &pool,
+        UserFilter::Age(I32Filter::Gt(18)),
+        col("id").asc(),
+        Pagination {
+            cursor: Cursor::I64,
+            after: None,
+            before: None,
+            first: Some(1),
+            last: None,
+            count: CountStrategy::Estimated,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(conn.additional_fields.total_count, 2);
+    assert!(!conn.additional_fields.total_count_is_estimate);
+}
+
+#[tokio::test]
+async fn test_sqlite_load_page_raw() {
+    let pool = pool().await;
+
+    let page = load_page_raw::<_, _, _, _, User>(
+        Driver::<Sqlite>::empty(),
+        &pool,
+        UserFilter::Age(I32Filter::Gt(18)),
+        col("id").asc(),
+        Pagination {
+            cursor: Cursor::I64,
+            after: None,
+            before: None,
+            first: Some(1),
+            last: None,
+            count: CountStrategy::Exact,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(page.items.len(), 1);
+    assert_eq!(page.items[0].item.id, 2);
+    assert_eq!(
+        page.page_info.end_cursor.as_ref(),
+        Some(&page.items[0].cursor)
+    );
+    assert!(page.page_info.has_next_page);
+    assert_eq!(page.total_count, 2);
+
+    let json = serde_json::to_value(&page).unwrap();
+    assert_eq!(json["items"][0]["item"]["email"], "b@lsor.dev");
+    assert_eq!(json["page_info"]["has_next_page"], true);
+    assert_eq!(json["total_count"], 2);
+}
+
+#[tokio::test]
+async fn test_sqlite_scan() {
+    let pool = pool().await;
+    for (id, email, age) in [(4, "d@lsor.dev", 50), (5, "e@lsor.dev", 60)] {
+        save_one(
+            Driver::<Sqlite>::empty(),
+            &pool,
+            User {
+                id,
+                email: email.to_string(),
+                age,
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    let mut rows = std::pin::pin!(scan::<_, _, _, _, User>(
+        &pool,
+        UserFilter::Age(I32Filter::Gt(0)),
+        col("age").asc(),
+        2,
+    ));
+    let mut ids = Vec::new();
+    while let Some(user) = rows.try_next().await.unwrap() {
+        if ids.is_empty() {
+            // rows that are inserted or deleted while scanning do not cause
+            // other rows to be skipped or repeated
+            for (id, email, age) in [(6, "f@lsor.dev", 70), (7, "g@lsor.dev", 1)] {
+                save_one(
+                    Driver::<Sqlite>::empty(),
+                    &pool,
+                    User {
+                        id,
+                        email: email.to_string(),
+                        age,
+                    },
+                )
+                .await
+                .unwrap();
+            }
+            sqlx::query("DELETE FROM users WHERE id = 4")
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        ids.push(user.id);
+    }
+    assert_eq!(ids, vec![1, 2, 3, 5, 6]);
+}
+
+#[tokio::test]
+async fn test_sqlite_load_offset_page() {
+    let pool = pool().await;
+    for (id, email, age) in [(4, "d@lsor.dev", 50), (5, "e@lsor.dev", 60)] {
+        save_one(
+            Driver::<Sqlite>::empty(),
+            &pool,
+            User {
+                id,
+                email: email.to_string(),
+                age,
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    let page = |page| {
+        load_offset_page::<_, _, _, _, User>(
+            Driver::<Sqlite>::empty(),
+            &pool,
+            UserFilter::Age(I32Filter::Gt(0)),
+            UserSort::Id(I64Sort::Desc),
+            OffsetPagination { page, per_page: 2 },
+        )
+    };
+
+    let page_2 = page(2).await.unwrap();
+    assert_eq!(
+        page_2.items.iter().map(|user| user.id).collect::<Vec<_>>(),
+        vec![3, 2]
+    );
+    assert_eq!(page_2.page, 2);
+    assert_eq!(page_2.total_pages, 3);
+    assert_eq!(page_2.total_count, 5);
+
+    let page_3 = page(3).await.unwrap();
+    assert_eq!(
+        page_3.items.iter().map(|user| user.id).collect::<Vec<_>>(),
+        vec![1]
+    );
+
+    // pages past the end are empty, but still know how many pages there are
+    let page_4 = page(4).await.unwrap();
+    assert!(page_4.items.is_empty());
+    assert_eq!(page_4.total_pages, 3);
+}
+
+#[tokio::test]
+async fn test_sqlite_load_page_with_ties() {
+    let pool = pool().await;
+    save_one(
+        Driver::<Sqlite>::empty(),
+        &pool,
+        User {
+            id: 4,
+            email: "d@lsor.dev".to_string(),
+            age: 21,
+        },
+    )
+    .await
+    .unwrap();
+
+    let mut after = None;
+    let mut ids = Vec::new();
+    for _ in 0..2 {
+        let conn = load_page::<_, _, _, _, User>(
+            Driver::<Sqlite>::empty(),
+            &pool,
+            UserFilter::Age(I32Filter::Gt(0)),
+            col("age").desc(),
+            Pagination {
+                cursor: Cursor::I32,
+                after: after.clone(),
+                before: None,
+                first: Some(2),
+                last: None,
+                count: CountStrategy::Exact,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(conn.edges.len(), 2);
+        assert_eq!(conn.additional_fields.total_count, 4);
+        ids.extend(conn.edges.iter().map(|edge| edge.node.id));
+        after = conn.edges.last().map(|edge| edge.cursor.clone());
+    }
+    // ties on age are broken by the primary key, in the same direction
+    assert_eq!(ids, vec![3, 4, 2, 1]);
+}
+
+#[tokio::test]
+async fn test_sqlite_load_page_with_empty_sort() {
+    let pool = pool().await;
+
+    let mut after = None;
+    let mut ids = Vec::new();
+    for n in [2, 1] {
+        // the cursor is for the email, but an empty sorting only sorts by the
+        // primary key
+        let conn = load_page::<_, _, _, _, User>(
+            Driver::<Sqlite>::empty(),
+            &pool,
+            UserFilter::Age(I32Filter::Gt(0)),
+            multi_sort(Vec::<UserSort>::new()),
+            Pagination {
+                cursor: Cursor::String,
+                after: after.clone(),
+                before: None,
+                first: Some(2),
+                last: None,
+                count: CountStrategy::None,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(conn.edges.len(), n);
+        ids.extend(conn.edges.iter().map(|edge| edge.node.id));
+        after = conn.edges.last().map(|edge| edge.cursor.clone());
+    }
+    assert_eq!(ids, vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn test_sqlite_load_page_with_null_sort_keys() {
+    let pool = pool().await;
+    sqlx::query("CREATE TABLE scores (id INTEGER PRIMARY KEY, score INTEGER)")
+        .execute(&pool)
+        .await
+        .unwrap();
+    for (id, score) in [
+        (1, Some(20)),
+        (2, None),
+        (3, Some(10)),
+        (4, None),
+        (5, Some(20)),
+    ] {
+        save_one(Driver::<Sqlite>::empty(), &pool, Score { id, score })
+            .await
+            .unwrap();
+    }
+
+    for (sort, expected) in [
+        (col("score").asc(), vec![2, 4, 3, 1, 5]),
+        (col("score").desc(), vec![5, 1, 3, 4, 2]),
+    ] {
+        let mut after = None;
+        let mut ids = Vec::new();
+        loop {
+            let conn = load_page::<_, _, _, _, Score>(
+                Driver::<Sqlite>::empty(),
+                &pool,
+                col("id").gt(0),
+                &sort,
+                Pagination {
+                    cursor: Cursor::I32,
+                    after: after.clone(),
+                    before: None,
+                    first: Some(2),
+                    last: None,
+                    count: CountStrategy::None,
+                },
+            )
+            .await
+            .unwrap();
+            ids.extend(conn.edges.iter().map(|edge| edge.node.id));
+            after = conn.edges.last().map(|edge| edge.cursor.clone());
+            if !conn.has_next_page {
+                break;
+            }
+        }
+        // rows with a NULL sort key are neither dropped nor repeated
+        assert_eq!(ids, expected);
+    }
+}
+
+#[tokio::test]
+async fn test_sqlite_load_page_with_signed_cursors() {
+    let pool = pool().await;
+    let signer = CursorSigner::new("secret");
+    let pagination = |after| Pagination {
+        cursor: Cursor::I64,
+        after,
+        before: None,
+        first: Some(2),
+        last: None,
+        count: CountStrategy::None,
+    };
+
+    let conn = load_page::<_, _, _, _, User>(
+        Driver::<Sqlite>::empty().with_cursor_signer(signer.clone()),
+        &pool,
+        UserFilter::Age(I32Filter::Gt(0)),
+        col("id").asc(),
+        pagination(None),
+    )
+    .await
+    .unwrap();
+    let end = conn.edges.last().unwrap().cursor.clone();
+
+    let conn = load_page::<_, _, _, _, User>(
+        Driver::<Sqlite>::empty().with_cursor_signer(signer.clone()),
+        &pool,
+        UserFilter::Age(I32Filter::Gt(0)),
+        col("id").asc(),
+        pagination(Some(end)),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        conn.edges
+            .iter()
+            .map(|edge| edge.node.id)
+            .collect::<Vec<_>>(),
+        vec![3]
+    );
+
+    // a cursor crafted by the client is rejected instead of being ignored
+    let result = load_page::<_, _, _, _, User>(
+        Driver::<Sqlite>::empty().with_cursor_signer(signer),
+        &pool,
+        UserFilter::Age(I32Filter::Gt(0)),
+        col("id").asc(),
+        pagination(Some(
+            "Zm9yZ2VkIGN1cnNvciB0aGF0IGlzIGxvbmcgZW5vdWdoIHRvIGJlIHNpZ25lZA==".to_string(),
+        )),
+    )
+    .await;
+    assert!(result.is_err_and(|err| err.is_cursor()));
+}
+
+#[tokio::test]
+async fn test_sqlite_relay_pagination() {
+    let pool = pool().await;
+    for (id, email, age) in [(4, "d@lsor.dev", 50), (5, "e@lsor.dev", 60)] {
+        save_one(
+            Driver::<Sqlite>::empty(),
+            &pool,
+            User {
+                id,
+                email: email.to_string(),
+                age,
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    let page = |sort: I64Sort, after: Option<i64>, before: Option<i64>, first, last| {
+        let pool = pool.clone();
+        async move {
+            // cursors are opaque, so find them by paginating over everything
+            let all = load_page::<_, _, _, _, User>(
+                Driver::<Sqlite>::empty(),
+                &pool,
+                UserFilter::Age(I32Filter::Gt(0)),
+                UserSort::Id(sort),
+                Pagination {
+                    cursor: Cursor::I64,
+                    after: None,
+                    before: None,
+                    first: None,
+                    last: None,
+                    count: CountStrategy::None,
+                },
+            )
+            .await
+            .unwrap();
+            let cursor = |id: i64| {
+                all.edges
+                    .iter()
+                    .find(|edge| edge.node.id == id)
+                    .map(|edge| edge.cursor.clone())
+            };
+
+            let conn = load_page::<_, _, _, _, User>(
+                Driver::<Sqlite>::empty(),
+                &pool,
+                UserFilter::Age(I32Filter::Gt(0)),
+                UserSort::Id(sort),
+                Pagination {
+                    cursor: Cursor::I64,
+                    after: after.and_then(cursor),
+                    before: before.and_then(cursor),
+                    first,
+                    last,
+                    count: CountStrategy::None,
+                },
+            )
+            .await
+            .unwrap();
+            (
+                conn.edges
+                    .iter()
+                    .map(|edge| edge.node.id)
+                    .collect::<Vec<_>>(),
+                conn.has_previous_page,
+                conn.has_next_page,
+            )
+        }
+    };
+
+    // (sort, after, before, first, last) => (ids, has_previous_page, has_next_page)
+    let cases = [
+        (
+            (I64Sort::Asc, Some(2), None, Some(2), None),
+            (vec![3, 4], true, true),
+        ),
+        (
+            (I64Sort::Asc, None, Some(4), Some(2), None),
+            (vec![1, 2], false, true),
+        ),
+        (
+            (I64Sort::Asc, Some(2), None, None, Some(2)),
+            (vec![4, 5], true, false),
+        ),
+        (
+            (I64Sort::Asc, None, Some(4), None, Some(2)),
+            (vec![2, 3], true, true),
+        ),
+        (
+            (I64Sort::Asc, Some(1), Some(5), Some(3), Some(2)),
+            (vec![3, 4], true, true),
+        ),
+        (
+            (I64Sort::Desc, Some(4), None, Some(2), None),
+            (vec![3, 2], true, true),
+        ),
+        (
+            (I64Sort::Desc, None, Some(2), Some(2), None),
+            (vec![5, 4], false, true),
+        ),
+        (
+            (I64Sort::Desc, Some(4), None, None, Some(2)),
+            (vec![2, 1], true, false),
+        ),
+        (
+            (I64Sort::Desc, None, Some(2), None, Some(2)),
+            (vec![4, 3], true, true),
+        ),
+        (
+            (I64Sort::Desc, Some(5), Some(1), Some(3), Some(2)),
+            (vec![3, 2], true, true),
+        ),
+        (
+            (I64Sort::Asc, Some(5), None, Some(2), None),
+            (vec![], true, false),
+        ),
+        (
+            (I64Sort::Desc, None, Some(5), None, Some(2)),
+            (vec![], false, true),
+        ),
+    ];
+    for ((sort, after, before, first, last), expected) in cases {
+        assert_eq!(
+            page(sort, after, before, first, last).await,
+            expected,
+            "sort={:?} after={:?} before={:?} first={:?} last={:?}",
+            sort,
+            after,
+            before,
+            first,
+            last
+        );
+    }
+}